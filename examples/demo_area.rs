@@ -1,4 +1,3 @@
-use std::thread;
 use std::time::Duration;
 use talos::Talos;
 use talos::input::{Event, KeyCode, KeyEvent};
@@ -15,7 +14,8 @@ fn main() -> Result<(), talos::error::TalosError> {
 
     while running {
         // 2. Handle Input
-        if let Some(events) = talos.poll_input()? {
+        // Sleeps until input arrives - but at most for one frame
+        if let Some(events) = talos.wait_input(Some(Duration::from_millis(16)))? {
             for event in events {
                 match event {
                     // Quit on 'q' or Esc
@@ -76,9 +76,6 @@ fn main() -> Result<(), talos::error::TalosError> {
 
         // 4. Present to Terminal
         talos.present()?;
-
-        // Cap framerate slightly to save CPU
-        thread::sleep(Duration::from_millis(16));
     }
 
     Ok(())
@@ -7,7 +7,6 @@ use talos::{
 };
 
 // A simple helper to make the loop cleaner
-use std::thread;
 use std::time::Duration;
 
 fn main() -> Result<(), talos::TalosError> {
@@ -18,7 +17,8 @@ fn main() -> Result<(), talos::TalosError> {
 
     while running {
         // 2. Handle Input
-        if let Some(events) = talos.poll_input()? {
+        // Sleeps until input arrives - but at most for one frame
+        if let Some(events) = talos.wait_input(Some(Duration::from_millis(16)))? {
             for event in events {
                 match event {
                     // Quit on 'q' or Esc
@@ -115,9 +115,6 @@ fn main() -> Result<(), talos::TalosError> {
 
         // 4. Present to Terminal
         talos.present()?;
-
-        // Cap framerate slightly to save CPU
-        thread::sleep(Duration::from_millis(16));
     }
 
     Ok(())
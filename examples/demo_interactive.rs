@@ -1,4 +1,3 @@
-use std::thread;
 use std::time::Duration;
 use talos::{
     LayoutBuilder, Talos,
@@ -30,7 +29,8 @@ fn main() -> Result<(), talos::TalosError> {
 
     while running {
         let mut text_changed = false;
-        if let Some(events) = talos.poll_input()? {
+        // Sleeps until input arrives - but at most for one frame
+        if let Some(events) = talos.wait_input(Some(Duration::from_millis(16)))? {
             for event in events {
                 match event {
                     Event::KeyEvent(KeyEvent {
@@ -149,7 +149,6 @@ fn main() -> Result<(), talos::TalosError> {
         help_text.render(canvas, chunks[3], thoth);
 
         talos.present()?;
-        thread::sleep(Duration::from_millis(16));
     }
 
     Ok(())
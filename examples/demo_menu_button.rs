@@ -1,4 +1,3 @@
-use std::thread;
 use std::time::Duration;
 use talos::{
     LayoutBuilder, Talos,
@@ -39,7 +38,8 @@ fn main() -> Result<(), talos::TalosError> {
     let mut sub_menu_rect = Rect::default();
 
     while running {
-        if let Some(events) = talos.poll_input()? {
+        // Sleeps until input arrives - but at most for one frame
+        if let Some(events) = talos.wait_input(Some(Duration::from_millis(16)))? {
             for event in events {
                 match event {
                     Event::MouseEvent(MouseEvent {
@@ -243,7 +243,6 @@ fn main() -> Result<(), talos::TalosError> {
         }
 
         talos.present()?;
-        thread::sleep(Duration::from_millis(16));
     }
 
     Ok(())
@@ -0,0 +1,341 @@
+use std::time::Duration;
+
+/// How long `TerminalIO` waits for the terminal to answer the capability queries
+///
+/// Every terminal answers the primary device attributes query, which is sent last - so waiting
+/// ends as soon as that answer arrives. The timeout only matters for unresponsive terminals.
+pub(crate) const PROBE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The number of colours a terminal can display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColourDepth {
+    /// No colours at all - e.g. `TERM=dumb` or `NO_COLOR` set
+    Monochrome,
+    /// The 8 normal and 8 bright colours
+    Ansi16,
+    /// The 256 colour palette
+    Ansi256,
+    /// 24-bit RGB colours
+    TrueColour,
+}
+
+/// What the terminal supports
+///
+/// Detected once by `TalosBuilder::build`, from `$TERM`, `$COLORTERM` and `$NO_COLOR` and by
+/// querying the terminal itself (device attributes, `XTVERSION` and `DECRQM` for the modes below).
+/// Retrieve it with `Talos::capabilities`.
+///
+/// The `Default` describes a modern terminal supporting everything but the kitty keyboard
+/// protocol, which is only assumed if the terminal answered - this is what `Talos` assumes for
+/// backends that do not probe, like the `TestBackend`.
+///
+/// # Example
+/// ```rust,no_run
+/// use talos::{Talos, backend::ColourDepth};
+///
+/// let talos = Talos::builder().build().unwrap();
+/// if talos.capabilities().colour_depth < ColourDepth::TrueColour {
+///     // Pick a palette that looks good with fewer colours
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Capabilities {
+    /// The number of colours the terminal can display
+    pub colour_depth: ColourDepth,
+    /// Synchronized updates (mode `?2026`) - frames are drawn without tearing
+    pub synchronized_update: bool,
+    /// SGR mouse encoding (mode `?1006`) - without it, `TerminalIO` only requests the urxvt
+    /// encoding
+    pub sgr_mouse: bool,
+    /// Bracketed paste (mode `?2004`) - without it, `TerminalIO` does not request it
+    pub bracketed_paste: bool,
+    /// Focus reporting (mode `?1004`) - without it, `TerminalIO` does not request it
+    pub focus_events: bool,
+    /// The kitty keyboard protocol - see `KeyboardEnhancement`
+    pub kitty_keyboard: bool,
+    /// Name and version of the terminal, as reported by `XTVERSION` - e.g. `"kitty(0.35.2)"`
+    pub terminal_version: Option<String>,
+    /// The parameters of the primary device attributes (DA1) answer
+    pub primary_attributes: Vec<u16>,
+    /// The parameters of the secondary device attributes (DA2) answer
+    pub secondary_attributes: Vec<u16>,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            colour_depth: ColourDepth::TrueColour,
+            synchronized_update: true,
+            sgr_mouse: true,
+            bracketed_paste: true,
+            focus_events: true,
+            kitty_keyboard: false,
+            terminal_version: None,
+            primary_attributes: Vec::new(),
+            secondary_attributes: Vec::new(),
+        }
+    }
+}
+
+impl Capabilities {
+    /// Guesses the capabilities from the environment of the process alone
+    ///
+    /// Only the colour depth is taken from the environment. Synchronized updates and the kitty
+    /// keyboard protocol are assumed to be unsupported, everything else to be supported.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let term = std::env::var("TERM").ok();
+        let colorterm = std::env::var("COLORTERM").ok();
+        let no_colour = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        Self {
+            colour_depth: colour_depth_from_env(term.as_deref(), colorterm.as_deref(), no_colour),
+            synchronized_update: false,
+            ..Self::default()
+        }
+    }
+
+    /// Applies the answers of the terminal to the probe
+    ///
+    /// Everything the terminal answered is taken as is, even if the probe timed out. Modes the
+    /// terminal did not report on are considered unsupported - but only if it answered the
+    /// primary device attributes (always answered last) and some mode query at all.
+    pub(crate) fn apply(&mut self, responses: &ProbeResponses) {
+        if let Some(primary_attributes) = &responses.primary_attributes {
+            self.primary_attributes.clone_from(primary_attributes);
+        }
+        if let Some(secondary_attributes) = &responses.secondary_attributes {
+            self.secondary_attributes.clone_from(secondary_attributes);
+        }
+        if responses.terminal_version.is_some() {
+            self.terminal_version
+                .clone_from(&responses.terminal_version);
+        }
+
+        // Terminals without `DECRQM` answer no mode query - keep the assumptions for them
+        let complete = responses.primary_attributes.is_some() && !responses.modes.is_empty();
+        let supported = |mode: u16, assumed: bool| {
+            responses
+                .modes
+                .iter()
+                .find(|&&(m, _)| m == mode)
+                .map_or(assumed && !complete, |&(_, state)| matches!(state, 1..=3))
+        };
+        self.synchronized_update = supported(2026, self.synchronized_update);
+        self.sgr_mouse = supported(1006, self.sgr_mouse);
+        self.bracketed_paste = supported(2004, self.bracketed_paste);
+        self.focus_events = supported(1004, self.focus_events);
+        // Only terminals supporting the protocol answer the keyboard flags query
+        if responses.keyboard_flags.is_some() {
+            self.kitty_keyboard = true;
+        }
+
+        // Plenty of terminals support truecolour without advertising it in the environment
+        if self.colour_depth > ColourDepth::Monochrome
+            && let Some(version) = &self.terminal_version
+            && is_truecolour_terminal(version)
+        {
+            self.colour_depth = ColourDepth::TrueColour;
+        }
+    }
+}
+
+fn colour_depth_from_env(
+    term: Option<&str>,
+    colorterm: Option<&str>,
+    no_colour: bool,
+) -> ColourDepth {
+    if no_colour {
+        return ColourDepth::Monochrome;
+    }
+    if matches!(colorterm, Some("truecolor" | "24bit")) {
+        return ColourDepth::TrueColour;
+    }
+    match term {
+        None | Some("" | "dumb") => ColourDepth::Monochrome,
+        Some(term) if term.contains("truecolor") || term.contains("direct") => {
+            ColourDepth::TrueColour
+        }
+        Some(term) if term.contains("256") => ColourDepth::Ansi256,
+        Some(_) => ColourDepth::Ansi16,
+    }
+}
+
+fn is_truecolour_terminal(version: &str) -> bool {
+    const TRUECOLOUR_TERMINALS: [&str; 8] = [
+        "kitty",
+        "wezterm",
+        "foot",
+        "iterm2",
+        "alacritty",
+        "ghostty",
+        "contour",
+        "konsole",
+    ];
+    let version = version.to_lowercase();
+    TRUECOLOUR_TERMINALS
+        .iter()
+        .any(|name| version.starts_with(name))
+}
+
+/// The answers of the terminal to the capability queries
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct ProbeResponses {
+    /// `(mode, state)` pairs of all `DECRPM` answers
+    pub modes: Vec<(u16, u16)>,
+    /// The active kitty keyboard flags - only answered by terminals supporting the protocol
+    pub keyboard_flags: Option<u16>,
+    pub terminal_version: Option<String>,
+    pub primary_attributes: Option<Vec<u16>>,
+    pub secondary_attributes: Option<Vec<u16>>,
+}
+
+/// Splits `bytes` into the answers to the capability queries and everything else
+///
+/// Everything that is not an answer (e.g. keys pressed while probing) is returned, to be handed to
+/// the input parser. Incomplete sequences at the end are returned as well.
+pub(crate) fn parse_probe_responses(bytes: &[u8]) -> (ProbeResponses, Vec<u8>) {
+    let mut responses = ProbeResponses::default();
+    let mut rest = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let consumed = match bytes.get(i..i + 2) {
+            Some([0x1B, b'[']) => parse_csi_response(&bytes[i + 2..], &mut responses),
+            Some([0x1B, b'P']) => parse_dcs_response(&bytes[i + 2..], &mut responses),
+            _ => None,
+        };
+        if let Some(len) = consumed {
+            i += len + 2;
+        } else {
+            rest.push(bytes[i]);
+            i += 1;
+        }
+    }
+    (responses, rest)
+}
+
+/// Returns the length of the answer after `ESC [`, or `None` if this is no (complete) answer
+fn parse_csi_response(bytes: &[u8], responses: &mut ProbeResponses) -> Option<usize> {
+    let end = bytes.iter().position(|b| (0x40..=0x7E).contains(b))?;
+    let body = &bytes[..end];
+    let (prefix, body) = match body.first() {
+        Some(b'?' | b'>') => (Some(body[0]), &body[1..]),
+        _ => (None, body),
+    };
+    let (body, intermediate) = match body.last() {
+        Some(b'$') => (&body[..body.len() - 1], true),
+        _ => (body, false),
+    };
+    let params = parse_params(body)?;
+
+    match (prefix, intermediate, bytes[end]) {
+        (Some(b'?'), false, b'c') => responses.primary_attributes = Some(params),
+        (Some(b'>'), false, b'c') => responses.secondary_attributes = Some(params),
+        (Some(b'?'), false, b'u') => {
+            responses.keyboard_flags = Some(params.first().copied().unwrap_or(0));
+        }
+        (Some(b'?'), true, b'y') => {
+            let (&mode, &state) = (params.first()?, params.get(1)?);
+            responses.modes.push((mode, state));
+        }
+        _ => return None,
+    }
+    Some(end + 1)
+}
+
+/// Returns the length of the answer after `ESC P`, or `None` if this is no (complete) answer
+fn parse_dcs_response(bytes: &[u8], responses: &mut ProbeResponses) -> Option<usize> {
+    let end = bytes.windows(2).position(|w| w == [0x1B, b'\\'])?;
+    let version = bytes[..end].strip_prefix(b">|")?;
+    responses.terminal_version = Some(String::from_utf8_lossy(version).into_owned());
+    Some(end + 2)
+}
+
+fn parse_params(body: &[u8]) -> Option<Vec<u16>> {
+    if body.is_empty() {
+        return Some(Vec::new());
+    }
+    body.split(|&b| b == b';')
+        .map(|param| std::str::from_utf8(param).ok()?.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colour_depth_from_env() {
+        let depth = colour_depth_from_env;
+        assert_eq!(
+            depth(Some("xterm-256color"), None, false),
+            ColourDepth::Ansi256
+        );
+        assert_eq!(
+            depth(Some("xterm-256color"), Some("truecolor"), false),
+            ColourDepth::TrueColour
+        );
+        assert_eq!(depth(Some("xterm"), None, false), ColourDepth::Ansi16);
+        assert_eq!(depth(Some("dumb"), None, false), ColourDepth::Monochrome);
+        assert_eq!(depth(None, None, false), ColourDepth::Monochrome);
+        assert_eq!(
+            depth(Some("xterm-256color"), Some("24bit"), true),
+            ColourDepth::Monochrome
+        );
+    }
+
+    #[test]
+    fn test_parse_probe_responses() {
+        let bytes = b"\x1b[?2026;2$y\x1b[?1006;0$yq\x1b[?0u\x1bP>|kitty(0.35.2)\x1b\\\x1b[>1;4000;29c\x1b[A\x1b[?62;22c\x1b[";
+        let (responses, rest) = parse_probe_responses(bytes);
+
+        assert_eq!(responses.modes, vec![(2026, 2), (1006, 0)]);
+        assert_eq!(responses.keyboard_flags, Some(0));
+        assert_eq!(responses.terminal_version.as_deref(), Some("kitty(0.35.2)"));
+        assert_eq!(responses.secondary_attributes, Some(vec![1, 4000, 29]));
+        assert_eq!(responses.primary_attributes, Some(vec![62, 22]));
+        // Input and incomplete sequences are left for the input parser
+        assert_eq!(rest, b"q\x1b[A\x1b[");
+
+        let mut capabilities = Capabilities {
+            colour_depth: ColourDepth::Ansi256,
+            ..Capabilities::default()
+        };
+        capabilities.apply(&responses);
+        assert!(capabilities.synchronized_update);
+        assert!(!capabilities.sgr_mouse);
+        assert!(!capabilities.bracketed_paste);
+        assert!(capabilities.kitty_keyboard);
+        assert_eq!(capabilities.colour_depth, ColourDepth::TrueColour);
+    }
+
+    #[test]
+    fn test_unanswered_probe_keeps_assumptions() {
+        let (responses, rest) = parse_probe_responses(b"x");
+        let mut capabilities = Capabilities::default();
+        capabilities.apply(&responses);
+        assert_eq!(capabilities, Capabilities::default());
+        assert_eq!(rest, b"x");
+    }
+
+    #[test]
+    fn test_partial_probe_responses() {
+        // Timed out before the primary device attributes - the answers so far still count
+        let (responses, rest) = parse_probe_responses(b"\x1b[?2026;0$yx\x1b[?1u");
+        let mut capabilities = Capabilities::default();
+        capabilities.apply(&responses);
+        assert!(!capabilities.synchronized_update);
+        assert!(capabilities.bracketed_paste);
+        assert!(capabilities.kitty_keyboard);
+        assert_eq!(rest, b"x");
+
+        // No `DECRQM` support - the modes are not judged
+        let (responses, _) = parse_probe_responses(b"\x1b[?62;22c");
+        let mut capabilities = Capabilities::default();
+        capabilities.apply(&responses);
+        assert!(capabilities.sgr_mouse);
+        assert!(!capabilities.kitty_keyboard);
+        assert_eq!(capabilities.primary_attributes, vec![62, 22]);
+    }
+}
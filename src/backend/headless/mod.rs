@@ -0,0 +1,744 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    io::{self, Read, Write},
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::{backend::Backend, error::TalosResult, input::Event, render::CCell};
+
+mod screen;
+use screen::Screen;
+
+struct Inner {
+    screen: Screen,
+    written: Vec<u8>,
+    input_bytes: VecDeque<u8>,
+    input_events: Vec<Event>,
+    entered: bool,
+    resized: bool,
+    waits: usize,
+}
+
+/// A headless, in-memory backend for tests
+///
+/// Everything `Talos` writes is captured and interpreted back into a grid of
+/// [`CCell`](../render/struct.CCell.html)s, which can then be asserted on.
+/// Input can be injected either as raw bytes (parsed by the input parser like real terminal input)
+/// or as ready-made `Event`s.
+///
+/// The backend is a cheap handle to shared state - keep a clone around to inspect the screen after
+/// handing it to `Talos`.
+///
+/// # Example
+/// ```rust
+/// use talos::{
+///     Talos,
+///     backend::TestBackend,
+///     input::{Event, KeyCode, KeyEvent, KeyModifiers},
+///     layout::Rect,
+///     widgets::{Text, traits::Widget},
+/// };
+///
+/// let backend = TestBackend::new(20, 3);
+/// let mut talos = Talos::builder()
+///     .with_backend(Box::new(backend.clone()))
+///     .without_panic_handler()
+///     .build()?;
+///
+/// talos.begin_frame();
+/// let (canvas, thoth) = talos.render_ctx();
+/// let mut text = Text::new("Hello", thoth);
+/// text.render(canvas, Rect::new(0, 0, 20, 1), thoth);
+/// talos.present()?;
+///
+/// assert_eq!(backend.line(0).trim_end(), "Hello");
+///
+/// backend.inject_bytes(b"q");
+/// let events = talos.poll_input()?.unwrap_or_default();
+/// assert_eq!(
+///     events,
+///     &[Event::KeyEvent(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::default()))]
+/// );
+/// # Ok::<(), talos::TalosError>(())
+/// ```
+#[derive(Clone)]
+pub struct TestBackend {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl TestBackend {
+    /// Creates a new `TestBackend` with a fixed size
+    ///
+    /// # Arguments
+    /// * `width` - The width of the screen
+    /// * `height` - The height of the screen
+    #[must_use]
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(Inner {
+                screen: Screen::new(width, height),
+                written: Vec::new(),
+                input_bytes: VecDeque::new(),
+                input_events: Vec::new(),
+                entered: false,
+                resized: false,
+                waits: 0,
+            })),
+        }
+    }
+
+    /// Queues raw input bytes, returned by the next `Talos::poll_input`
+    ///
+    /// The bytes are parsed by the input parser, exactly like terminal input.
+    pub fn inject_bytes(&self, bytes: &[u8]) {
+        self.inner.borrow_mut().input_bytes.extend(bytes);
+    }
+
+    /// Queues an event, returned by the next `Talos::poll_input`
+    ///
+    /// Injected events are returned after any parsed bytes.
+    pub fn inject_event(&self, event: Event) {
+        self.inner.borrow_mut().input_events.push(event);
+    }
+
+    /// Resizes the screen, like a user resizing their terminal window
+    ///
+    /// The screen is cleared. `Talos` picks up the new size on the next `present` or
+    /// `poll_input`.
+    pub fn resize(&self, width: u16, height: u16) {
+        let mut inner = self.inner.borrow_mut();
+        inner.screen.resize(width, height);
+        inner.resized = true;
+    }
+
+    /// Returns the cell at the given position. Returns default (space) if out of bounds.
+    #[must_use]
+    pub fn get_ccell(&self, x: u16, y: u16) -> CCell {
+        self.inner.borrow().screen.get_ccell(x, y)
+    }
+
+    /// Returns a copy of all cells of the screen, row by row
+    #[must_use]
+    pub fn buffer(&self) -> Vec<CCell> {
+        self.inner.borrow().screen.cells().to_vec()
+    }
+
+    /// Returns the glyphs of the given row as a string
+    ///
+    /// Returns an empty string if the row is out of bounds.
+    #[must_use]
+    pub fn line(&self, y: u16) -> String {
+        let inner = self.inner.borrow();
+        let (width, height) = inner.screen.size();
+        if y >= height {
+            return String::new();
+        }
+        (0..width)
+            .map(|x| inner.screen.get_ccell(x, y).char)
+            .fold(String::new(), |mut out, g| {
+                out.push_str(g.as_str());
+                out
+            })
+    }
+
+    /// Returns the glyphs of the whole screen, one row per line
+    #[must_use]
+    pub fn contents(&self) -> String {
+        let (_, height) = self.inner.borrow().screen.size();
+        (0..height)
+            .map(|y| self.line(y))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Returns the position of the cursor in the form `(x, y)`
+    #[must_use]
+    pub fn cursor(&self) -> (u16, u16) {
+        self.inner.borrow().screen.cursor()
+    }
+
+    /// Returns all bytes written to the backend since the last call to `clear_written`
+    #[must_use]
+    pub fn written(&self) -> Vec<u8> {
+        self.inner.borrow().written.clone()
+    }
+
+    /// Clears the captured bytes
+    ///
+    /// The screen is not affected.
+    pub fn clear_written(&self) {
+        self.inner.borrow_mut().written.clear();
+    }
+
+    /// Returns whether the backend is currently entered (between `enter` and `restore`)
+    #[must_use]
+    pub fn is_entered(&self) -> bool {
+        self.inner.borrow().entered
+    }
+
+    /// Returns how often `Talos` waited for input
+    ///
+    /// The backend never blocks - this shows whether `Talos::wait_input` would have.
+    #[must_use]
+    pub fn waits(&self) -> usize {
+        self.inner.borrow().waits
+    }
+}
+
+impl Backend for TestBackend {
+    fn size(&self) -> TalosResult<(u16, u16)> {
+        Ok(self.inner.borrow().screen.size())
+    }
+
+    fn enter(&mut self) -> TalosResult<()> {
+        self.inner.borrow_mut().entered = true;
+        Ok(())
+    }
+
+    fn restore(&mut self) -> TalosResult<()> {
+        self.inner.borrow_mut().entered = false;
+        Ok(())
+    }
+
+    fn poll_resize(&mut self) -> bool {
+        std::mem::take(&mut self.inner.borrow_mut().resized)
+    }
+
+    fn poll_events(&mut self, output: &mut Vec<Event>) {
+        output.append(&mut self.inner.borrow_mut().input_events);
+    }
+
+    fn wait_for_input(&mut self, _timeout: Option<Duration>) -> TalosResult<()> {
+        self.inner.borrow_mut().waits += 1;
+        Ok(())
+    }
+}
+
+impl Read for TestBackend {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut inner = self.inner.borrow_mut();
+        let n = buf.len().min(inner.input_bytes.len());
+        for (slot, byte) in buf.iter_mut().zip(inner.input_bytes.drain(..n)) {
+            *slot = byte;
+        }
+        Ok(n)
+    }
+}
+
+impl Write for TestBackend {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut inner = self.inner.borrow_mut();
+        inner.written.extend_from_slice(buf);
+        inner.screen.process(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Talos,
+        backend::ColourDepth,
+        input::{KeyCode, KeyEvent, KeyModifiers, Query},
+        layout::Rect,
+        render::{
+            Bright, Colour, ColourMode, CursorShape, Extended, Grapheme, Normal, Style, TrueColour,
+        },
+        widgets::{Block, Text, traits::Widget},
+    };
+
+    fn make_talos(backend: &TestBackend) -> TalosResult<Talos> {
+        Talos::builder()
+            .with_backend(Box::new(backend.clone()))
+            .without_panic_handler()
+            .build()
+    }
+
+    #[test]
+    fn test_present_renders_into_screen() -> TalosResult<()> {
+        let backend = TestBackend::new(10, 3);
+        let mut talos = make_talos(&backend)?;
+        assert!(backend.is_entered());
+
+        let style = Style::builder()
+            .set_fg(Colour::Normal(Normal::Red))
+            .set_bold(true)
+            .build();
+
+        talos.begin_frame();
+        let (canvas, thoth) = talos.render_ctx();
+        let mut block = Block::new();
+        block.style(style);
+        block.render(canvas, Rect::new(0, 0, 4, 3), thoth);
+        talos.present()?;
+
+        assert_eq!(backend.line(0), "┌──┐      ");
+        assert_eq!(backend.line(1), "│  │      ");
+        assert_eq!(backend.line(2), "└──┘      ");
+        assert_eq!(backend.get_ccell(0, 0).style, style);
+        assert_eq!(backend.get_ccell(5, 0).style, Style::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_present_only_writes_changes() -> TalosResult<()> {
+        let backend = TestBackend::new(10, 2);
+        let mut talos = make_talos(&backend)?;
+
+        talos.begin_frame();
+        let (canvas, thoth) = talos.render_ctx();
+        Text::new("Hello", thoth).render(canvas, Rect::new(0, 0, 10, 1), thoth);
+        talos.present()?;
+
+        backend.clear_written();
+        talos.begin_frame();
+        let (canvas, thoth) = talos.render_ctx();
+        Text::new("Help", thoth).render(canvas, Rect::new(0, 0, 10, 1), thoth);
+        talos.present()?;
+
+        let written = String::from_utf8_lossy(&backend.written()).to_string();
+        assert!(written.contains('p'));
+        assert!(!written.contains("Hel"));
+        assert_eq!(backend.contents(), "Help      \n          ");
+        Ok(())
+    }
+
+    #[test]
+    fn test_injected_input() -> TalosResult<()> {
+        let backend = TestBackend::new(10, 2);
+        let mut talos = make_talos(&backend)?;
+
+        let injected = Event::KeyEvent(KeyEvent::new(KeyCode::Enter, KeyModifiers::default()));
+        backend.inject_bytes(b"a\x1b[A");
+        backend.inject_event(injected.clone());
+
+        let events = talos.poll_input()?.unwrap_or_default().to_vec();
+        assert_eq!(
+            events,
+            vec![
+                Event::KeyEvent(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::default())),
+                Event::KeyEvent(KeyEvent::new(KeyCode::Up, KeyModifiers::default())),
+                injected,
+            ]
+        );
+
+        assert!(talos.poll_input()?.unwrap_or_default().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resize_event() -> TalosResult<()> {
+        let backend = TestBackend::new(10, 2);
+        let mut talos = make_talos(&backend)?;
+
+        backend.resize(20, 4);
+        backend.inject_bytes(b"a");
+        let events = talos.poll_input()?.unwrap_or_default().to_vec();
+        assert_eq!(
+            events,
+            vec![
+                Event::Resize {
+                    width: 20,
+                    height: 4
+                },
+                Event::KeyEvent(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::default())),
+            ]
+        );
+        assert_eq!(talos.canvas_mut().size_rect(), Rect::new(0, 0, 20, 4));
+
+        // A resize noticed by `present` is still reported by the next `poll_input`
+        backend.resize(8, 3);
+        assert!(matches!(talos.present()?, crate::Present::Resized));
+        let events = talos.poll_input()?.unwrap_or_default().to_vec();
+        assert_eq!(
+            events,
+            vec![Event::Resize {
+                width: 8,
+                height: 3
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_present_moves_between_rows() -> TalosResult<()> {
+        let backend = TestBackend::new(6, 2);
+        let mut talos = make_talos(&backend)?;
+
+        // Adjacent columns on different rows
+        talos.begin_frame();
+        let (canvas, thoth) = talos.render_ctx();
+        Text::new("a", thoth).render(canvas, Rect::new(2, 0, 1, 1), thoth);
+        Text::new("b", thoth).render(canvas, Rect::new(3, 1, 1, 1), thoth);
+        talos.present()?;
+        assert_eq!(backend.contents(), "  a   \n   b  ");
+
+        // The first changed cell in the first column of an inline viewport
+        let backend = TestBackend::new(6, 3);
+        let mut talos = Talos::builder()
+            .with_backend(Box::new(backend.clone()))
+            .with_inline_viewport(2)
+            .without_panic_handler()
+            .build()?;
+        talos.begin_frame();
+        let (canvas, thoth) = talos.render_ctx();
+        Text::new("two", thoth).render(canvas, Rect::new(0, 1, 6, 1), thoth);
+        talos.present()?;
+        assert_eq!(backend.line(0).trim_end(), "");
+        assert_eq!(backend.line(1).trim_end(), "two");
+        Ok(())
+    }
+
+    #[test]
+    fn test_resize_reserves_inline_viewport() -> TalosResult<()> {
+        let backend = TestBackend::new(6, 4);
+        let mut talos = Talos::builder()
+            .with_backend(Box::new(backend.clone()))
+            .with_inline_viewport(3)
+            .without_panic_handler()
+            .build()?;
+        talos.present()?;
+
+        backend.resize(8, 4);
+        backend.clear_written();
+        talos.poll_input()?;
+        let written = String::from_utf8_lossy(&backend.written()).into_owned();
+        assert_eq!(written.matches('\n').count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wait_input_returns_injected_input() -> TalosResult<()> {
+        let backend = TestBackend::new(10, 2);
+        let mut talos = make_talos(&backend)?;
+
+        backend.inject_bytes(b"x");
+        let events = talos
+            .wait_input(Some(std::time::Duration::from_secs(5)))?
+            .unwrap_or_default();
+        assert_eq!(
+            events,
+            &[Event::KeyEvent(KeyEvent::new(
+                KeyCode::Char('x'),
+                KeyModifiers::default()
+            ))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_waits_for_answer() -> TalosResult<()> {
+        let backend = TestBackend::new(10, 2);
+        let mut talos = make_talos(&backend)?;
+        let timeout = std::time::Duration::from_millis(50);
+
+        backend.clear_written();
+        backend.inject_bytes(b"a\x1b[2;3R");
+        let answer = talos.query(Query::CursorPosition, timeout)?;
+        assert_eq!(answer, Some(Event::CursorPosition { column: 2, row: 1 }));
+        assert_eq!(backend.written(), b"\x1b[6n");
+
+        // Input arriving in the meantime is not lost
+        let events = talos.poll_input()?.unwrap_or_default().to_vec();
+        assert_eq!(
+            events,
+            vec![Event::KeyEvent(KeyEvent::new(
+                KeyCode::Char('a'),
+                KeyModifiers::default()
+            ))]
+        );
+
+        // No answer
+        assert_eq!(talos.query(Query::BackgroundColour, timeout)?, None);
+
+        // Given up on - the same bytes are Shift+F3 again
+        assert_eq!(talos.query(Query::CursorPosition, timeout)?, None);
+        backend.inject_bytes(b"\x1b[1;2R");
+        let shift = KeyModifiers {
+            shift: true,
+            none: false,
+            ..KeyModifiers::default()
+        };
+        assert_eq!(
+            talos.poll_input()?.unwrap_or_default(),
+            &[Event::KeyEvent(KeyEvent::new(KeyCode::F(3), shift))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_wait_input_returns_input_read_by_query() -> TalosResult<()> {
+        let backend = TestBackend::new(10, 2);
+        let mut talos = make_talos(&backend)?;
+
+        backend.inject_bytes(b"a\x1b[2;3R");
+        talos.query(Query::CursorPosition, std::time::Duration::from_millis(50))?;
+        let waits = backend.waits();
+        let events = talos.wait_input(None)?.unwrap_or_default().to_vec();
+        assert_eq!(
+            events,
+            vec![Event::KeyEvent(KeyEvent::new(
+                KeyCode::Char('a'),
+                KeyModifiers::default()
+            ))]
+        );
+        // Nothing to wait for
+        assert_eq!(backend.waits(), waits);
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_suspended_redraws_everything() -> TalosResult<()> {
+        let backend = TestBackend::new(10, 2);
+        let mut talos = make_talos(&backend)?;
+
+        talos.begin_frame();
+        let (canvas, thoth) = talos.render_ctx();
+        Text::new("Hello", thoth).render(canvas, Rect::new(0, 0, 10, 1), thoth);
+        talos.present()?;
+
+        let entered = talos.with_suspended(|| backend.is_entered())?;
+        assert!(!entered);
+        assert!(backend.is_entered());
+        // The screen was cleared on resume
+        assert_eq!(backend.line(0).trim_end(), "");
+
+        talos.begin_frame();
+        let (canvas, thoth) = talos.render_ctx();
+        Text::new("Hello", thoth).render(canvas, Rect::new(0, 0, 10, 1), thoth);
+        talos.present()?;
+        assert_eq!(backend.line(0).trim_end(), "Hello");
+        Ok(())
+    }
+
+    #[test]
+    fn test_resize_while_suspended_is_delivered() -> TalosResult<()> {
+        let backend = TestBackend::new(10, 2);
+        let mut talos = make_talos(&backend)?;
+
+        talos.with_suspended(|| backend.resize(12, 3))?;
+        let waits = backend.waits();
+        let events = talos.wait_input(None)?.unwrap_or_default().to_vec();
+        // Reported once
+        assert_eq!(
+            events,
+            vec![Event::Resize {
+                width: 12,
+                height: 3
+            }]
+        );
+        assert_eq!(backend.waits(), waits);
+
+        // The inline viewport is reserved by `resume` alone
+        let backend = TestBackend::new(10, 4);
+        let mut talos = Talos::builder()
+            .with_backend(Box::new(backend.clone()))
+            .with_inline_viewport(2)
+            .without_panic_handler()
+            .build()?;
+        talos.with_suspended(|| backend.resize(12, 4))?;
+        backend.clear_written();
+        talos.wait_input(None)?;
+        assert!(backend.written().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_suspend_and_resume_are_idempotent() -> TalosResult<()> {
+        let backend = TestBackend::new(6, 4);
+        let mut talos = Talos::builder()
+            .with_backend(Box::new(backend.clone()))
+            .with_inline_viewport(2)
+            .without_panic_handler()
+            .build()?;
+        talos.present()?;
+
+        // Not suspended - nothing to resume
+        backend.clear_written();
+        talos.resume()?;
+        assert!(backend.written().is_empty());
+
+        talos.suspend()?;
+        backend.clear_written();
+        talos.suspend()?;
+        assert!(backend.written().is_empty());
+        assert!(!backend.is_entered());
+
+        talos.resume()?;
+        assert!(backend.is_entered());
+        backend.clear_written();
+        talos.resume()?;
+        assert!(backend.written().is_empty());
+
+        // Dropping while suspended leaves the terminal alone
+        talos.suspend()?;
+        backend.clear_written();
+        drop(talos);
+        assert!(backend.written().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_inline_viewport() -> TalosResult<()> {
+        let mut backend = TestBackend::new(6, 4);
+        // Shell output, with the cursor on the last line
+        let _ = backend.write(b"$ a\r\nout\r\n$ b\r\n");
+        backend.clear_written();
+
+        let mut talos = Talos::builder()
+            .with_backend(Box::new(backend.clone()))
+            .with_inline_viewport(2)
+            .without_panic_handler()
+            .build()?;
+        assert_eq!(talos.canvas_mut().size_rect(), Rect::new(0, 0, 6, 2));
+
+        talos.begin_frame();
+        let (canvas, thoth) = talos.render_ctx();
+        Text::new("one", thoth).render(canvas, Rect::new(0, 0, 6, 1), thoth);
+        Text::new("two", thoth).render(canvas, Rect::new(1, 1, 5, 1), thoth);
+        talos.present()?;
+        drop(talos);
+
+        // The screen scrolled to make room and to place the cursor below, but was never cleared
+        assert!(!String::from_utf8_lossy(&backend.written()).contains("\x1b[2J"));
+        assert_eq!(backend.contents(), "$ b   \none   \n two  \n      ");
+        assert_eq!(backend.cursor(), (0, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_before_inline_viewport() -> TalosResult<()> {
+        fn frame(talos: &mut Talos) -> TalosResult<()> {
+            talos.begin_frame();
+            let (canvas, thoth) = talos.render_ctx();
+            Text::new("live", thoth).render(canvas, Rect::new(0, 0, 6, 1), thoth);
+            talos.present().map(|_| ())
+        }
+
+        let backend = TestBackend::new(6, 4);
+        let mut talos = Talos::builder()
+            .with_backend(Box::new(backend.clone()))
+            .with_inline_viewport(2)
+            .without_panic_handler()
+            .build()?;
+
+        frame(&mut talos)?;
+        talos.insert_before(&["log 1", "log 2"])?;
+        frame(&mut talos)?;
+        assert_eq!(backend.contents(), "log 1 \nlog 2 \nlive  \n      ");
+
+        // Once the screen is full, older lines scroll out of view
+        talos.insert_before(&["log 3"])?;
+        frame(&mut talos)?;
+        assert_eq!(backend.contents(), "log 2 \nlog 3 \nlive  \n      ");
+
+        let mut fullscreen = make_talos(&TestBackend::new(6, 4))?;
+        assert!(matches!(
+            fullscreen.insert_before(&["nope"]),
+            Err(crate::TalosError::InvalidState)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_colour_depth_downgrades_colours() -> TalosResult<()> {
+        let backend = TestBackend::new(2, 1);
+        let mut talos = Talos::builder()
+            .with_backend(Box::new(backend.clone()))
+            .with_colour_depth(ColourDepth::Ansi256)
+            .without_panic_handler()
+            .build()?;
+
+        let style = Style::builder()
+            .set_fg(Colour::Extended(Extended::TrueColour(TrueColour::RGB(
+                255, 0, 0,
+            ))))
+            .set_bg(Colour::Normal(Normal::Blue))
+            .build();
+        let ccell = CCell {
+            char: Grapheme::new("x"),
+            style,
+        };
+
+        talos.begin_frame();
+        talos.canvas_mut().set_ccell(0, 0, ccell);
+        talos.present()?;
+        assert_eq!(
+            backend.get_ccell(0, 0).style,
+            Style::builder()
+                .set_fg(Colour::Extended(Extended::ColourMode(ColourMode::RgbBit(
+                    196
+                ))))
+                .set_bg(Colour::Normal(Normal::Blue))
+                .build()
+        );
+
+        talos.set_colour_depth(ColourDepth::Ansi16)?;
+        talos.begin_frame();
+        talos.canvas_mut().set_ccell(0, 0, ccell);
+        talos.present()?;
+        assert_eq!(
+            backend.get_ccell(0, 0).style,
+            Style::builder()
+                .set_fg(Colour::Bright(Bright::Red))
+                .set_bg(Colour::Normal(Normal::Blue))
+                .build()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_cursor() -> TalosResult<()> {
+        let backend = TestBackend::new(10, 3);
+        let mut talos = make_talos(&backend)?;
+
+        talos.set_cursor(Some((3, 1)));
+        talos.set_cursor_shape(CursorShape::SteadyBar);
+        talos.begin_frame();
+        let (canvas, thoth) = talos.render_ctx();
+        Text::new("Hello", thoth).render(canvas, Rect::new(0, 0, 10, 1), thoth);
+        talos.present()?;
+
+        let written = String::from_utf8_lossy(&backend.written()).to_string();
+        assert!(written.ends_with("\x1b[2;4H\x1b[?25h\x1b[6 q\x1b[?2026l"));
+        assert_eq!(backend.cursor(), (3, 1));
+
+        backend.clear_written();
+        talos.set_cursor(None);
+        talos.present()?;
+        let written = String::from_utf8_lossy(&backend.written()).to_string();
+        assert!(written.contains("\x1b[?25l"));
+        assert!(!written.contains(" q"));
+
+        drop(talos);
+        let written = String::from_utf8_lossy(&backend.written()).to_string();
+        assert!(written.ends_with("\x1b[0 q"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_screen_interprets_sgr_and_graphemes() {
+        let mut backend = TestBackend::new(4, 1);
+        let _ = backend.write(b"\x1b[1;3H\x1b[0;38;2;1;2;3;101;4mm\xcc\x80\x1b[0mz");
+
+        let rgb = Style::builder()
+            .set_fg(Colour::Extended(Extended::TrueColour(TrueColour::RGB(
+                1, 2, 3,
+            ))))
+            .set_bg(Colour::Bright(Bright::Red))
+            .set_underline(true)
+            .build();
+
+        assert_eq!(backend.get_ccell(2, 0).char, Grapheme::new("m̀"));
+        assert_eq!(backend.get_ccell(2, 0).style, rgb);
+        assert_eq!(backend.get_ccell(3, 0).char, Grapheme::new("z"));
+        assert_eq!(backend.get_ccell(3, 0).style, Style::default());
+    }
+}
@@ -0,0 +1,287 @@
+use crate::render::{CCell, Grapheme, Style};
+
+/// States for the screen state machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScreenState {
+    Normal,
+    Esc,
+    Csi,
+    Osc,
+    OscEsc,
+}
+
+/// A minimal terminal emulator
+///
+/// Interprets the bytes `Talos` writes back into a grid of `CCell`s.
+/// Only the control sequences `Talos` itself emits are understood, everything else is ignored.
+#[derive(Debug, Clone)]
+pub struct Screen {
+    width: u16,
+    height: u16,
+    cells: Vec<CCell>,
+    cursor: (u16, u16),
+    style: Style,
+    state: ScreenState,
+    params: Vec<u16>,
+    current_param: u16,
+    has_param_digit: bool,
+    is_private: bool,
+    utf8_buffer: Vec<u8>,
+    last_written: Option<usize>,
+    join_next: bool,
+}
+
+impl Screen {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![CCell::default(); width as usize * height as usize],
+            cursor: (0, 0),
+            style: Style::default(),
+            state: ScreenState::Normal,
+            params: Vec::with_capacity(8),
+            current_param: 0,
+            has_param_digit: false,
+            is_private: false,
+            utf8_buffer: Vec::with_capacity(4),
+            last_written: None,
+            join_next: false,
+        }
+    }
+
+    /// Resizes the screen - all cells are cleared
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.width = width;
+        self.height = height;
+        self.cells = vec![CCell::default(); width as usize * height as usize];
+        self.cursor = (0, 0);
+        self.last_written = None;
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    pub fn cursor(&self) -> (u16, u16) {
+        self.cursor
+    }
+
+    pub fn cells(&self) -> &[CCell] {
+        &self.cells
+    }
+
+    pub fn get_ccell(&self, x: u16, y: u16) -> CCell {
+        if x >= self.width || y >= self.height {
+            return CCell::default();
+        }
+        self.cells[self.index(x, y)]
+    }
+
+    pub fn process(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            match self.state {
+                ScreenState::Normal => self.handle_normal(byte),
+                ScreenState::Esc => self.handle_esc(byte),
+                ScreenState::Csi => self.handle_csi(byte),
+                ScreenState::Osc => match byte {
+                    0x07 => self.state = ScreenState::Normal,
+                    0x1B => self.state = ScreenState::OscEsc,
+                    _ => {}
+                },
+                ScreenState::OscEsc => {
+                    self.state = if byte == b'\\' {
+                        ScreenState::Normal
+                    } else {
+                        ScreenState::Osc
+                    };
+                }
+            }
+        }
+    }
+
+    fn index(&self, x: u16, y: u16) -> usize {
+        x as usize + y as usize * self.width as usize
+    }
+
+    fn handle_normal(&mut self, byte: u8) {
+        match byte {
+            0x1B => {
+                self.utf8_buffer.clear();
+                self.state = ScreenState::Esc;
+            }
+            b'\r' => self.cursor.0 = 0,
+            b'\n' => self.line_feed(),
+            0x08 => self.cursor.0 = self.cursor.0.saturating_sub(1),
+            0x00..=0x1F | 0x7F => {}
+            _ => {
+                self.utf8_buffer.push(byte);
+                match std::str::from_utf8(&self.utf8_buffer) {
+                    Ok(s) => {
+                        if let Some(ch) = s.chars().next() {
+                            self.put_char(ch);
+                        }
+                        self.utf8_buffer.clear();
+                    }
+                    // Incomplete sequence - wait for more bytes
+                    Err(e) if e.error_len().is_none() => {}
+                    Err(_) => self.utf8_buffer.clear(),
+                }
+            }
+        }
+    }
+
+    fn handle_esc(&mut self, byte: u8) {
+        match byte {
+            b'[' => {
+                self.params.clear();
+                self.current_param = 0;
+                self.has_param_digit = false;
+                self.is_private = false;
+                self.state = ScreenState::Csi;
+            }
+            b']' => self.state = ScreenState::Osc,
+            _ => self.state = ScreenState::Normal,
+        }
+    }
+
+    fn handle_csi(&mut self, byte: u8) {
+        match byte {
+            b'0'..=b'9' => {
+                self.current_param = self
+                    .current_param
+                    .saturating_mul(10)
+                    .saturating_add(u16::from(byte - b'0'));
+                self.has_param_digit = true;
+            }
+            b';' => {
+                self.params.push(self.current_param);
+                self.current_param = 0;
+                self.has_param_digit = false;
+            }
+            b'?' | b'<' | b'=' | b'>' => self.is_private = true,
+            0x40..=0x7E => {
+                if self.has_param_digit {
+                    self.params.push(self.current_param);
+                }
+                if !self.is_private {
+                    self.finalize_csi(byte);
+                }
+                self.state = ScreenState::Normal;
+            }
+            _ => {}
+        }
+    }
+
+    fn finalize_csi(&mut self, final_byte: u8) {
+        let first = self.params.first().copied().unwrap_or(0);
+        let amount = first.max(1);
+        match final_byte {
+            b'H' | b'f' => {
+                let row = first.max(1) - 1;
+                let col = self.params.get(1).copied().unwrap_or(1).max(1) - 1;
+                self.cursor = (
+                    col.min(self.width.saturating_sub(1)),
+                    row.min(self.height.saturating_sub(1)),
+                );
+            }
+            b'A' => self.cursor.1 = self.cursor.1.saturating_sub(amount),
+            b'B' => {
+                self.cursor.1 = self
+                    .cursor
+                    .1
+                    .saturating_add(amount)
+                    .min(self.height.saturating_sub(1));
+            }
+            b'C' => {
+                self.cursor.0 = self
+                    .cursor
+                    .0
+                    .saturating_add(amount)
+                    .min(self.width.saturating_sub(1));
+            }
+            b'D' => self.cursor.0 = self.cursor.0.saturating_sub(amount),
+            b'J' => {
+                let len = self.cells.len();
+                let cursor = self.index(self.cursor.0, self.cursor.1).min(len);
+                match first {
+                    0 => self.cells[cursor..].fill(CCell::default()),
+                    1 => self.cells[..(cursor + 1).min(len)].fill(CCell::default()),
+                    _ => self.cells.fill(CCell::default()),
+                }
+            }
+            b'K' => {
+                if self.cursor.1 >= self.height {
+                    return;
+                }
+                let start = self.index(0, self.cursor.1);
+                let end = start + self.width as usize;
+                let cursor = self.index(self.cursor.0, self.cursor.1).min(end);
+                match first {
+                    0 => self.cells[cursor..end].fill(CCell::default()),
+                    1 => self.cells[start..(cursor + 1).min(end)].fill(CCell::default()),
+                    _ => self.cells[start..end].fill(CCell::default()),
+                }
+            }
+            b'm' => self.style.apply_sgr(&self.params),
+            _ => {}
+        }
+    }
+
+    fn line_feed(&mut self) {
+        if self.cursor.1.saturating_add(1) < self.height {
+            self.cursor.1 += 1;
+        } else {
+            // Scroll everything up by one line
+            self.cells.drain(..self.width as usize);
+            self.cells
+                .extend(std::iter::repeat_n(CCell::default(), self.width as usize));
+            self.last_written = None;
+        }
+    }
+
+    fn put_char(&mut self, ch: char) {
+        let mut encoded = [0u8; 4];
+        let encoded = ch.encode_utf8(&mut encoded);
+
+        // Combining characters are part of the previous grapheme
+        if is_zero_width(ch) || self.join_next {
+            self.join_next = ch == '\u{200D}';
+            if let Some(idx) = self.last_written {
+                let mut joined = self.cells[idx].char.as_str().to_string();
+                joined.push_str(encoded);
+                self.cells[idx].char = Grapheme::new(&joined);
+            }
+            return;
+        }
+
+        // Talos never relies on line wrapping, so anything past the right edge is clipped
+        if self.cursor.0 >= self.width || self.cursor.1 >= self.height {
+            return;
+        }
+
+        let idx = self.index(self.cursor.0, self.cursor.1);
+        self.cells[idx] = CCell {
+            char: Grapheme::new(encoded),
+            style: self.style,
+        };
+        self.last_written = Some(idx);
+        self.cursor.0 += 1;
+    }
+}
+
+/// Returns whether the char is rendered as part of the preceding grapheme
+fn is_zero_width(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{200C}'..='\u{200D}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            | '\u{E0100}'..='\u{E01EF}'
+    )
+}
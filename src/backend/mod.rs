@@ -1,3 +1,141 @@
-pub mod sys;
+//! This module contains the backends `Talos` can render to and read input from.
+//!
+//! The default backend is [`TerminalIO`](struct.TerminalIO.html), which uses the stdin and stdout
+//! of the process.
+//!
+//! If you want to render somewhere else (e.g. into memory for tests, or over a socket), you can
+//! implement the [`Backend`](trait.Backend.html) trait and hand it to
+//! [`TalosBuilder::with_backend`](../struct.TalosBuilder.html#method.with_backend).
+//!
+//! For tests without a terminal, use the [`TestBackend`](struct.TestBackend.html).
+
+use std::{
+    io::{Read, Write},
+    time::Duration,
+};
+
+use crate::{error::TalosResult, input::Event};
+
+mod capabilities;
+pub(crate) use capabilities::PROBE_TIMEOUT;
+pub use capabilities::{Capabilities, ColourDepth};
+pub(crate) mod sys;
 mod terminal;
 pub use terminal::TerminalIO;
+mod headless;
+pub use headless::TestBackend;
+
+/// The backend interface trait
+///
+/// A backend is anything `Talos` can write escape codes to and read input bytes from.
+///
+/// * Writing is done through the `Write` implementation. `Talos` will call `flush` once per
+///   presented frame.
+/// * Reading is done through the `Read` implementation. Reads must never block - if there is no
+///   input available, return `Ok(0)`.
+///
+/// Backends should also restore themselves when dropped.
+///
+/// # Example
+/// ```rust
+/// use std::io::{Read, Write};
+/// use talos::{backend::Backend, error::TalosResult};
+///
+/// struct NullBackend;
+///
+/// impl Read for NullBackend {
+///     fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+///         Ok(0)
+///     }
+/// }
+///
+/// impl Write for NullBackend {
+///     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+///         Ok(buf.len())
+///     }
+///
+///     fn flush(&mut self) -> std::io::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// impl Backend for NullBackend {
+///     fn size(&self) -> TalosResult<(u16, u16)> {
+///         Ok((80, 24))
+///     }
+///
+///     fn enter(&mut self) -> TalosResult<()> {
+///         Ok(())
+///     }
+///
+///     fn restore(&mut self) -> TalosResult<()> {
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait Backend: Read + Write {
+    /// Returns the size of the backend in the form `(width, height)`
+    ///
+    /// # Errors
+    /// Returns an error if the size could not be determined
+    fn size(&self) -> TalosResult<(u16, u16)>;
+    /// Prepares the backend for rendering
+    ///
+    /// Called once by `TalosBuilder::build`, before the first frame is presented, and by
+    /// `Talos::resume`. Input modes like mouse reporting are enabled by `enable_modes` afterwards.
+    ///
+    /// # Errors
+    /// Returns an error if the backend could not be set up
+    fn enter(&mut self) -> TalosResult<()>;
+    /// Enables input modes like mouse reporting and bracketed paste
+    ///
+    /// Called right after `enter` - by `TalosBuilder::build` only once the capabilities were
+    /// probed, so that only supported modes are requested. `restore` disables them again.
+    /// The default implementation does nothing.
+    ///
+    /// # Errors
+    /// Returns an error if the modes could not be enabled
+    fn enable_modes(&mut self) -> TalosResult<()> {
+        Ok(())
+    }
+    /// Restores the backend to the state it was in before `enter` was called
+    ///
+    /// # Errors
+    /// Returns an error if the backend could not be restored
+    fn restore(&mut self) -> TalosResult<()>;
+    /// Pushes events the backend produced itself into `output`
+    ///
+    /// Called by `Talos::poll_input` after the bytes read from the backend have been parsed.
+    /// Backends that deliver all input as bytes do not need to implement this.
+    fn poll_events(&mut self, _output: &mut Vec<Event>) {}
+    /// Returns whether the backend was resized since the last call
+    ///
+    /// `Talos` also checks for `SIGWINCH` on its own - only backends that learn about resizes
+    /// some other way (e.g. from a remote client) need to implement this.
+    fn poll_resize(&mut self) -> bool {
+        false
+    }
+    /// Blocks until input may be available to read, or the timeout elapsed
+    ///
+    /// `None` as `timeout` waits until input arrives.
+    /// Backends should also wake up on OS signals, so that resizes are noticed promptly.
+    /// Waking up early is always allowed - the default implementation returns immediately.
+    ///
+    /// # Errors
+    /// Returns an error if waiting failed
+    fn wait_for_input(&mut self, _timeout: Option<Duration>) -> TalosResult<()> {
+        Ok(())
+    }
+    /// Detects what the backend supports
+    ///
+    /// Called once by `TalosBuilder::build`, right after `enter` and before `enable_modes`, unless
+    /// probing is disabled.
+    /// Input arriving while probing must not get lost - it has to be returned by later reads.
+    /// The default implementation returns `Capabilities::default()`.
+    ///
+    /// # Errors
+    /// Returns an error if probing failed
+    fn probe_capabilities(&mut self) -> TalosResult<Capabilities> {
+        Ok(Capabilities::default())
+    }
+}
@@ -1,4 +1,4 @@
-use std::os::fd;
+use std::{io, os::fd, time::Duration};
 
 #[cfg(unix)]
 mod unix;
@@ -34,3 +34,27 @@ pub fn check_resize() -> bool {
 pub fn check_terminate() -> bool {
     os::check_terminate()
 }
+
+pub fn check_suspend() -> bool {
+    os::check_suspend()
+}
+
+pub fn check_continue() -> bool {
+    os::check_continue()
+}
+
+pub fn suspend_process() -> TalosResult<()> {
+    os::suspend_process()
+}
+
+pub fn signal_pending() -> bool {
+    os::signal_pending()
+}
+
+pub fn read_fd(fd: fd::RawFd, buf: &mut [u8]) -> io::Result<usize> {
+    os::read_fd(fd, buf)
+}
+
+pub fn wait_readable(fd: fd::RawFd, timeout: Option<Duration>) -> TalosResult<()> {
+    os::wait_readable(fd, timeout)
+}
@@ -1,17 +1,34 @@
 use crate::error::TalosResult;
+use std::os::fd;
 use std::sync::Once;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
 use std::{io, mem, ptr};
 
 static RESIZE_NEEDED: AtomicBool = AtomicBool::new(false);
 static TERMINATE_NEEDED: AtomicBool = AtomicBool::new(false);
+static SUSPEND_NEEDED: AtomicBool = AtomicBool::new(false);
+static CONTINUE_NEEDED: AtomicBool = AtomicBool::new(false);
 static HANDLER_REGISTERED: Once = Once::new();
 
+// Self-pipe: the signal handler writes a byte into it, so that a `poll` waiting for input wakes up
+static WAKE_READ_FD: AtomicI32 = AtomicI32::new(-1);
+static WAKE_WRITE_FD: AtomicI32 = AtomicI32::new(-1);
+
 extern "C" fn signal_handler(sig: libc::c_int) {
     match sig {
         libc::SIGWINCH => RESIZE_NEEDED.store(true, Ordering::Relaxed),
         libc::SIGTERM | libc::SIGINT => TERMINATE_NEEDED.store(true, Ordering::Relaxed),
-        _ => {}
+        libc::SIGTSTP => SUSPEND_NEEDED.store(true, Ordering::Relaxed),
+        libc::SIGCONT => CONTINUE_NEEDED.store(true, Ordering::Relaxed),
+        _ => return,
+    }
+
+    let wake_fd = WAKE_WRITE_FD.load(Ordering::Relaxed);
+    if wake_fd >= 0 {
+        // `write` is async-signal-safe. If the pipe is full, a wake up is pending anyway.
+        unsafe {
+            libc::write(wake_fd, [1u8].as_ptr().cast(), 1);
+        }
     }
 }
 
@@ -20,6 +37,19 @@ pub fn register_signal_handlers() -> TalosResult<()> {
 
     HANDLER_REGISTERED.call_once(|| {
         unsafe {
+            let mut pipe_fds = [-1; 2];
+            if libc::pipe(pipe_fds.as_mut_ptr()) == 0 {
+                for pipe_fd in pipe_fds {
+                    let flags = libc::fcntl(pipe_fd, libc::F_GETFL);
+                    libc::fcntl(pipe_fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
+                    libc::fcntl(pipe_fd, libc::F_SETFD, libc::FD_CLOEXEC);
+                }
+                WAKE_READ_FD.store(pipe_fds[0], Ordering::Relaxed);
+                WAKE_WRITE_FD.store(pipe_fds[1], Ordering::Relaxed);
+            } else {
+                out = Err(io::Error::last_os_error().into());
+            }
+
             let mut sa: libc::sigaction = mem::zeroed();
 
             sa.sa_sigaction = signal_handler as *const () as usize;
@@ -39,6 +69,16 @@ pub fn register_signal_handlers() -> TalosResult<()> {
             if libc::sigaction(libc::SIGINT, &raw const sa, ptr::null_mut()) == -1 {
                 out = Err(io::Error::last_os_error().into());
             }
+
+            // Register SIGTSTP (Suspend via Ctrl+Z)
+            if libc::sigaction(libc::SIGTSTP, &raw const sa, ptr::null_mut()) == -1 {
+                out = Err(io::Error::last_os_error().into());
+            }
+
+            // Register SIGCONT (Resume via fg / kill -CONT)
+            if libc::sigaction(libc::SIGCONT, &raw const sa, ptr::null_mut()) == -1 {
+                out = Err(io::Error::last_os_error().into());
+            }
         }
     });
 
@@ -50,5 +90,59 @@ pub fn check_resize() -> bool {
 }
 
 pub fn check_terminate() -> bool {
-    TERMINATE_NEEDED.load(Ordering::Relaxed)
+    TERMINATE_NEEDED.swap(false, Ordering::Relaxed)
+}
+
+pub fn check_suspend() -> bool {
+    SUSPEND_NEEDED.swap(false, Ordering::Relaxed)
+}
+
+pub fn check_continue() -> bool {
+    CONTINUE_NEEDED.swap(false, Ordering::Relaxed)
+}
+
+/// Stops the process, like the default action of `SIGTSTP` would
+///
+/// Returns once the process has been continued again.
+pub fn suspend_process() -> TalosResult<()> {
+    unsafe {
+        let mut default: libc::sigaction = mem::zeroed();
+        default.sa_sigaction = libc::SIG_DFL;
+        let mut previous: libc::sigaction = mem::zeroed();
+
+        // Our own handler would just set the flag again - temporarily fall back to the default
+        if libc::sigaction(libc::SIGTSTP, &raw const default, &raw mut previous) == -1 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        // Blocks right here until `SIGCONT`
+        libc::raise(libc::SIGTSTP);
+
+        if libc::sigaction(libc::SIGTSTP, &raw const previous, ptr::null_mut()) == -1 {
+            return Err(io::Error::last_os_error().into());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the read end of the signal self-pipe, if signal handlers are registered
+pub fn signal_wake_fd() -> Option<fd::RawFd> {
+    let wake_fd = WAKE_READ_FD.load(Ordering::Relaxed);
+    if wake_fd >= 0 { Some(wake_fd) } else { None }
+}
+
+/// Empties the signal self-pipe
+pub fn drain_signal_wake_fd() {
+    if let Some(wake_fd) = signal_wake_fd() {
+        let mut buf = [0u8; 32];
+        // Non blocking - returns -1 with `EAGAIN` once empty
+        while unsafe { libc::read(wake_fd, buf.as_mut_ptr().cast(), buf.len()) } > 0 {}
+    }
+}
+
+pub fn signal_pending() -> bool {
+    RESIZE_NEEDED.load(Ordering::Relaxed)
+        || TERMINATE_NEEDED.load(Ordering::Relaxed)
+        || SUSPEND_NEEDED.load(Ordering::Relaxed)
+        || CONTINUE_NEEDED.load(Ordering::Relaxed)
 }
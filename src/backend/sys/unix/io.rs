@@ -0,0 +1,94 @@
+use crate::error::TalosResult;
+use std::{io, os::fd, time::Duration};
+
+use super::flags::{drain_signal_wake_fd, signal_wake_fd};
+
+/// Reads directly from the file descriptor, bypassing any buffering
+///
+/// Interrupted reads are retried.
+pub fn read_fd(fd: fd::RawFd, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+        let n = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+        if n >= 0 {
+            return Ok(n.unsigned_abs());
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+/// Blocks until the file descriptor is readable, a signal arrived or the timeout elapsed
+///
+/// `None` as `timeout` blocks until input or a signal arrives.
+pub fn wait_readable(fd: fd::RawFd, timeout: Option<Duration>) -> TalosResult<()> {
+    let wake_fd = signal_wake_fd();
+    let mut fds = [
+        libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        },
+        // Negative file descriptors are ignored by `poll`
+        libc::pollfd {
+            fd: wake_fd.unwrap_or(-1),
+            events: libc::POLLIN,
+            revents: 0,
+        },
+    ];
+
+    // Round up, so that very short timeouts do not turn into busy polling
+    let timeout_ms = timeout.map_or(-1, |t| {
+        i32::try_from(t.as_micros().div_ceil(1000)).unwrap_or(i32::MAX)
+    });
+
+    let res = unsafe { libc::poll(fds.as_mut_ptr(), 2, timeout_ms) };
+    if res == -1 {
+        let err = io::Error::last_os_error();
+        // A signal arrived - the signal flags are checked by the caller
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err.into());
+        }
+    }
+
+    if fds[1].revents & libc::POLLIN != 0 {
+        drain_signal_wake_fd();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_wait_readable() -> TalosResult<()> {
+        let mut pipe_fds = [-1; 2];
+        assert_eq!(unsafe { libc::pipe(pipe_fds.as_mut_ptr()) }, 0);
+        let [read_end, write_end] = pipe_fds;
+
+        // Nothing to read - waits for the timeout
+        let start = Instant::now();
+        wait_readable(read_end, Some(Duration::from_millis(20)))?;
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        // Input available - returns at once
+        let written = unsafe { libc::write(write_end, b"x".as_ptr().cast(), 1) };
+        assert_eq!(written, 1);
+        let start = Instant::now();
+        wait_readable(read_end, Some(Duration::from_secs(5)))?;
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let mut buf = [0u8; 4];
+        assert_eq!(read_fd(read_end, &mut buf)?, 1);
+        assert_eq!(buf[0], b'x');
+
+        unsafe {
+            libc::close(read_end);
+            libc::close(write_end);
+        }
+        Ok(())
+    }
+}
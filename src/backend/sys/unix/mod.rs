@@ -2,4 +2,10 @@ mod raw_mode;
 pub use raw_mode::{disable_rawmode, enable_rawmode};
 
 mod flags;
-pub use flags::{check_resize, check_terminate, register_signal_handlers};
+pub use flags::{
+    check_continue, check_resize, check_suspend, check_terminate, register_signal_handlers,
+    signal_pending, suspend_process,
+};
+
+mod io;
+pub use io::{read_fd, wait_readable};
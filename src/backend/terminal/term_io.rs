@@ -1,57 +1,335 @@
-use crate::backend::sys::terminal_size;
+use crate::backend::capabilities::parse_probe_responses;
+use crate::backend::sys::{read_fd, terminal_size, wait_readable};
+use crate::backend::{Backend, Capabilities, PROBE_TIMEOUT};
 use crate::error::TalosResult;
+use crate::input::{KeyboardEnhancement, MouseMode};
 use crate::utils::constants::ansi::{
-    CLEAR_ALL, DISABLE_MOUSE_FORMATTING_CODE, DISABLE_MOUSE_REPORTING_CODE, ENTER_ALT_SCREEN,
-    EXIT_ALT_SCREEN, HIDE_CURSOR, MOUSE_FORMATTING_CODE, MOUSE_REPORTING_CODE, SHOW_CURSOR,
-    TO_TOP_LEFT,
+    CLEAR_ALL, CONTROL_SEQUENCE_INTRO, DISABLE_BRACKETED_PASTE, DISABLE_FOCUS_EVENTS,
+    DISABLE_MOUSE_DRAG_REPORTING_CODE, DISABLE_MOUSE_FORMATTING_CODE,
+    DISABLE_MOUSE_MOTION_REPORTING_CODE, DISABLE_MOUSE_REPORTING_CODE,
+    DISABLE_MOUSE_URXVT_FORMATTING_CODE, ENABLE_BRACKETED_PASTE, ENABLE_FOCUS_EVENTS,
+    ENTER_ALT_SCREEN, EXIT_ALT_SCREEN, HIDE_CURSOR, MOUSE_DRAG_REPORTING_CODE,
+    MOUSE_FORMATTING_CODE, MOUSE_MOTION_REPORTING_CODE, MOUSE_REPORTING_CODE,
+    MOUSE_URXVT_FORMATTING_CODE, POP_KEYBOARD_FLAGS, QUERY_BRACKETED_PASTE_MODE,
+    QUERY_FOCUS_EVENTS_MODE, QUERY_KEYBOARD_FLAGS, QUERY_PRIMARY_ATTRIBUTES,
+    QUERY_SECONDARY_ATTRIBUTES, QUERY_SGR_MOUSE_MODE, QUERY_SYNC_UPDATE_MODE,
+    QUERY_TERMINAL_VERSION, SHOW_CURSOR, TO_TOP_LEFT,
 };
-use std::io::{self, Write};
-use std::os::fd::AsRawFd;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::os::fd::{AsRawFd, RawFd};
+use std::time::{Duration, Instant};
 
 use super::raw_mode::RawMode;
 
+/// The default backend - renders to the stdout and reads from the stdin of the process
+///
+/// Alternatively, it can use the controlling terminal `/dev/tty` directly, see `with_tty`.
+///
+/// # Example
+/// ```rust,no_run
+/// use talos::{Talos, backend::TerminalIO};
+///
+/// let terminal = TerminalIO::new(true, true);
+/// let talos = Talos::builder().with_backend(Box::new(terminal)).build();
+/// assert!(talos.is_ok());
+/// ```
+#[allow(clippy::struct_excessive_bools)]
 pub struct TerminalIO {
     stdin: io::Stdin,
     stdout: io::Stdout,
+    tty: Option<Tty>,
     raw_mode: Option<RawMode>,
+    /// Input read while probing, returned before anything else
+    pending_input: Vec<u8>,
+    hide_cursor: bool,
+    alternate_screen: bool,
+    inline: bool,
+    focus_events: bool,
+    keyboard_enhancement: KeyboardEnhancement,
+    mouse_mode: MouseMode,
+    /// What the terminal supports - everything is assumed until probed
+    capabilities: Capabilities,
+    /// The input modes enabled by `enable_modes`, disabled again on `restore`
+    enabled_modes: Option<Modes>,
 }
 
 impl TerminalIO {
-    pub fn new(hide_cursor: bool, alternate_screen: bool) -> TalosResult<TerminalIO> {
-        let stdin = io::stdin();
-        let mut stdout = io::stdout();
+    /// Creates a new `TerminalIO`
+    ///
+    /// The terminal is left untouched until `Backend::enter` is called.
+    ///
+    /// # Arguments
+    /// * `hide_cursor` - Whether to hide the terminal cursor
+    /// * `alternate_screen` - Whether to use the alternate screen
+    #[must_use]
+    pub fn new(hide_cursor: bool, alternate_screen: bool) -> TerminalIO {
+        TerminalIO {
+            stdin: io::stdin(),
+            stdout: io::stdout(),
+            tty: None,
+            raw_mode: None,
+            pending_input: Vec::new(),
+            hide_cursor,
+            alternate_screen,
+            inline: false,
+            focus_events: false,
+            keyboard_enhancement: KeyboardEnhancement::default(),
+            mouse_mode: MouseMode::default(),
+            capabilities: Capabilities::default(),
+            enabled_modes: None,
+        }
+    }
+
+    /// Creates a new `TerminalIO` for an inline viewport
+    ///
+    /// The screen is never cleared and the alternate screen is not used, so everything rendered
+    /// stays in the scrollback of the terminal. Mouse reporting is off, unless enabled with
+    /// `with_mouse_mode`.
+    ///
+    /// # Arguments
+    /// * `hide_cursor` - Whether to hide the terminal cursor
+    #[must_use]
+    pub fn new_inline(hide_cursor: bool) -> TerminalIO {
+        let mut terminal = TerminalIO::new(hide_cursor, false);
+        terminal.inline = true;
+        terminal.mouse_mode = MouseMode::Off;
+        terminal
+    }
+
+    /// Uses the controlling terminal `/dev/tty` instead of stdin and stdout
+    ///
+    /// This leaves stdin and stdout of the process free for data, e.g. for pickers reading their
+    /// items from a pipe and writing the selection to one.
+    ///
+    /// # Errors
+    /// Returns an error if `/dev/tty` cannot be opened, e.g. if the process has no controlling
+    /// terminal.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::backend::TerminalIO;
+    ///
+    /// let terminal = TerminalIO::new(true, true).with_tty();
+    /// assert!(terminal.is_ok());
+    /// ```
+    pub fn with_tty(mut self) -> TalosResult<TerminalIO> {
+        let input = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        let output = BufWriter::new(input.try_clone()?);
+        self.tty = Some(Tty { input, output });
+        Ok(self)
+    }
+
+    /// Enables focus reporting - the terminal sends `Event::FocusGained` and `Event::FocusLost`
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::{Talos, backend::TerminalIO};
+    ///
+    /// let terminal = TerminalIO::new(true, true).with_focus_events();
+    /// let talos = Talos::builder().with_backend(Box::new(terminal)).build();
+    /// ```
+    #[must_use]
+    pub fn with_focus_events(mut self) -> TerminalIO {
+        self.focus_events = true;
+        self
+    }
+
+    /// Requests the kitty keyboard protocol enhancements from the terminal
+    ///
+    /// The flags are pushed on `enter` and popped on `restore`, so the previous keyboard mode of
+    /// the terminal is restored.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::{backend::TerminalIO, input::KeyboardEnhancement};
+    ///
+    /// let terminal = TerminalIO::new(true, true).with_keyboard_enhancement(KeyboardEnhancement {
+    ///     disambiguate_escape_codes: true,
+    ///     ..KeyboardEnhancement::default()
+    /// });
+    /// ```
+    #[must_use]
+    pub fn with_keyboard_enhancement(mut self, enhancement: KeyboardEnhancement) -> TerminalIO {
+        self.keyboard_enhancement = enhancement;
+        self
+    }
+
+    /// Sets which mouse events the terminal reports
+    ///
+    /// The default is `MouseMode::Press`, or `MouseMode::Off` for inline viewports.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::{backend::TerminalIO, input::MouseMode};
+    ///
+    /// let terminal = TerminalIO::new(true, true).with_mouse_mode(MouseMode::Drag);
+    /// ```
+    #[must_use]
+    pub fn with_mouse_mode(mut self, mouse_mode: MouseMode) -> TerminalIO {
+        self.mouse_mode = mouse_mode;
+        self
+    }
+
+    /// The codes enabling and disabling the reporting of the mouse mode
+    fn mouse_mode_codes(&self) -> Option<(&'static str, &'static str)> {
+        match self.mouse_mode {
+            MouseMode::Off => None,
+            MouseMode::Press => Some((MOUSE_REPORTING_CODE, DISABLE_MOUSE_REPORTING_CODE)),
+            MouseMode::Drag => Some((MOUSE_DRAG_REPORTING_CODE, DISABLE_MOUSE_DRAG_REPORTING_CODE)),
+            MouseMode::AnyMotion => Some((
+                MOUSE_MOTION_REPORTING_CODE,
+                DISABLE_MOUSE_MOTION_REPORTING_CODE,
+            )),
+        }
+    }
+
+    fn input_fd(&self) -> RawFd {
+        match &self.tty {
+            Some(tty) => tty.input.as_raw_fd(),
+            None => self.stdin.as_raw_fd(),
+        }
+    }
+
+    fn output_fd(&self) -> RawFd {
+        match &self.tty {
+            Some(tty) => tty.output.get_ref().as_raw_fd(),
+            None => self.stdout.as_raw_fd(),
+        }
+    }
 
-        let raw_mode = RawMode::enable(stdin.as_raw_fd())?;
+    fn output(&mut self) -> &mut dyn Write {
+        match &mut self.tty {
+            Some(tty) => &mut tty.output,
+            None => &mut self.stdout,
+        }
+    }
+
+    /// The input modes to request, given what the terminal supports
+    fn modes(&self) -> Modes {
+        Modes {
+            mouse: self.mouse_mode_codes(),
+            sgr_mouse: self.capabilities.sgr_mouse,
+            bracketed_paste: self.capabilities.bracketed_paste,
+            focus_events: self.focus_events && self.capabilities.focus_events,
+            keyboard_flags: self.keyboard_enhancement.flags(),
+        }
+    }
+
+    /// Writes everything needed to hand the terminal back - the raw mode is left as is
+    fn write_restore(&mut self) -> io::Result<()> {
+        if !self.inline {
+            write!(self.output(), "{CLEAR_ALL}")?;
+        }
+        // Exactly what was enabled - whatever was probed since
+        if let Some(modes) = self.enabled_modes.take() {
+            modes.write_disable(self.output())?;
+        }
+        if self.alternate_screen {
+            write!(self.output(), "{EXIT_ALT_SCREEN}")?;
+        }
+        write!(self.output(), "{SHOW_CURSOR}")?;
+        self.output().flush()
+    }
+}
+
+/// The input modes requested from the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+struct Modes {
+    /// The codes enabling and disabling mouse reporting
+    mouse: Option<(&'static str, &'static str)>,
+    sgr_mouse: bool,
+    bracketed_paste: bool,
+    focus_events: bool,
+    keyboard_flags: u8,
+}
+
+impl Modes {
+    fn write_enable(&self, out: &mut dyn Write) -> io::Result<()> {
+        if let Some((enable, _)) = self.mouse {
+            write!(out, "{MOUSE_URXVT_FORMATTING_CODE}")?;
+            // Takes precedence over the urxvt encoding where supported
+            if self.sgr_mouse {
+                write!(out, "{MOUSE_FORMATTING_CODE}")?;
+            }
+            write!(out, "{enable}")?;
+        }
+        if self.bracketed_paste {
+            write!(out, "{ENABLE_BRACKETED_PASTE}")?;
+        }
+        if self.focus_events {
+            write!(out, "{ENABLE_FOCUS_EVENTS}")?;
+        }
+        if self.keyboard_flags != 0 {
+            write!(out, "{CONTROL_SEQUENCE_INTRO}>{}u", self.keyboard_flags)?;
+        }
+        Ok(())
+    }
+
+    fn write_disable(&self, out: &mut dyn Write) -> io::Result<()> {
+        if let Some((_, disable)) = self.mouse {
+            write!(out, "{disable}")?;
+            if self.sgr_mouse {
+                write!(out, "{DISABLE_MOUSE_FORMATTING_CODE}")?;
+            }
+            write!(out, "{DISABLE_MOUSE_URXVT_FORMATTING_CODE}")?;
+        }
+        if self.bracketed_paste {
+            write!(out, "{DISABLE_BRACKETED_PASTE}")?;
+        }
+        if self.focus_events {
+            write!(out, "{DISABLE_FOCUS_EVENTS}")?;
+        }
+        if self.keyboard_flags != 0 {
+            write!(out, "{POP_KEYBOARD_FLAGS}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Backend for TerminalIO {
+    fn size(&self) -> TalosResult<(u16, u16)> {
+        let (rows, cols) = terminal_size(self.output_fd())?;
+        Ok((cols, rows))
+    }
+
+    fn enter(&mut self) -> TalosResult<()> {
+        if self.raw_mode.is_none() {
+            self.raw_mode = Some(RawMode::enable(self.input_fd())?);
+        }
 
         // Enter Alternate Screen
         // Clear Screen & Home Cursor
         // Hide Cursor
-        if alternate_screen {
-            write!(stdout, "{ENTER_ALT_SCREEN}")?;
+        if self.alternate_screen {
+            write!(self.output(), "{ENTER_ALT_SCREEN}")?;
         }
-        if hide_cursor {
-            write!(stdout, "{HIDE_CURSOR}")?;
+        if self.hide_cursor {
+            write!(self.output(), "{HIDE_CURSOR}")?;
         }
-        write!(stdout, "{CLEAR_ALL}")?;
-        write!(stdout, "{TO_TOP_LEFT}")?;
-        write!(stdout, "{MOUSE_FORMATTING_CODE}")?;
-        write!(stdout, "{MOUSE_REPORTING_CODE}")?;
-        stdout.flush()?;
+        // Inline viewports render below whatever is already on the screen
+        if !self.inline {
+            write!(self.output(), "{CLEAR_ALL}")?;
+            write!(self.output(), "{TO_TOP_LEFT}")?;
+        }
+        self.output().flush()?;
+        Ok(())
+    }
 
-        Ok(TerminalIO {
-            stdin,
-            stdout,
-            raw_mode: Some(raw_mode),
-        })
+    fn enable_modes(&mut self) -> TalosResult<()> {
+        if self.enabled_modes.is_some() {
+            return Ok(());
+        }
+        let modes = self.modes();
+        modes.write_enable(self.output())?;
+        self.output().flush()?;
+        self.enabled_modes = Some(modes);
+        Ok(())
     }
 
-    pub fn restore(&mut self) -> TalosResult<()> {
-        write!(self.stdout, "{CLEAR_ALL}")?;
-        write!(self.stdout, "{EXIT_ALT_SCREEN}")?;
-        write!(self.stdout, "{SHOW_CURSOR}")?;
-        write!(self.stdout, "{DISABLE_MOUSE_REPORTING_CODE}")?;
-        write!(self.stdout, "{DISABLE_MOUSE_FORMATTING_CODE}")?;
-        self.stdout.flush()?;
+    fn restore(&mut self) -> TalosResult<()> {
+        self.write_restore()?;
 
         if let Some(raw_mode) = self.raw_mode.take() {
             drop(raw_mode);
@@ -59,28 +337,61 @@ impl TerminalIO {
         Ok(())
     }
 
-    pub fn stdin(&mut self) -> &mut io::Stdin {
-        &mut self.stdin
+    fn wait_for_input(&mut self, timeout: Option<Duration>) -> TalosResult<()> {
+        if !self.pending_input.is_empty() {
+            return Ok(());
+        }
+        wait_readable(self.input_fd(), timeout)
     }
 
-    pub fn stdout(&mut self) -> &mut io::Stdout {
-        &mut self.stdout
-    }
+    fn probe_capabilities(&mut self) -> TalosResult<Capabilities> {
+        let mut capabilities = Capabilities::from_env();
+        for query in [
+            QUERY_SYNC_UPDATE_MODE,
+            QUERY_SGR_MOUSE_MODE,
+            QUERY_BRACKETED_PASTE_MODE,
+            QUERY_FOCUS_EVENTS_MODE,
+            QUERY_KEYBOARD_FLAGS,
+            QUERY_TERMINAL_VERSION,
+            QUERY_SECONDARY_ATTRIBUTES,
+            QUERY_PRIMARY_ATTRIBUTES,
+        ] {
+            write!(self.output(), "{query}")?;
+        }
+        self.output().flush()?;
 
-    pub fn size(&self) -> TalosResult<(u16, u16)> {
-        terminal_size(self.stdout.as_raw_fd())
+        let deadline = Instant::now() + PROBE_TIMEOUT;
+        let mut received = Vec::new();
+        let mut buf = [0u8; 256];
+        loop {
+            let (responses, rest) = parse_probe_responses(&received);
+            // The primary device attributes are always answered last - on timeout, take whatever
+            // an unresponsive terminal answered so far
+            let now = Instant::now();
+            if responses.primary_attributes.is_some() || now >= deadline {
+                capabilities.apply(&responses);
+                self.pending_input.extend_from_slice(&rest);
+                break;
+            }
+            wait_readable(self.input_fd(), Some(deadline - now))?;
+            let n = read_fd(self.input_fd(), &mut buf)?;
+            received.extend_from_slice(&buf[..n]);
+        }
+        // `enable_modes` only requests what is supported
+        self.capabilities.clone_from(&capabilities);
+        Ok(capabilities)
     }
 }
 
 impl Drop for TerminalIO {
     fn drop(&mut self) {
+        // Never entered or already restored - nothing to do
+        if self.raw_mode.is_none() {
+            return;
+        }
+
         // Also wtf am I supposed to do with errors in here
-        let _ = write!(self.stdout, "{CLEAR_ALL}");
-        let _ = write!(self.stdout, "{EXIT_ALT_SCREEN}");
-        let _ = write!(self.stdout, "{SHOW_CURSOR}");
-        let _ = write!(self.stdout, "{DISABLE_MOUSE_REPORTING_CODE}");
-        let _ = write!(self.stdout, "{DISABLE_MOUSE_FORMATTING_CODE}");
-        let _ = self.stdout.flush();
+        let _ = self.write_restore();
 
         // Lets be explicit with dropping the raw mode - better safe than sorry
         if let Some(raw_mode) = self.raw_mode.take() {
@@ -89,13 +400,102 @@ impl Drop for TerminalIO {
     }
 }
 
+// Reads bypass the buffer of `io::Stdin` - otherwise `wait_for_input` could block while input is
+// already buffered
+impl Read for TerminalIO {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.pending_input.is_empty() {
+            let n = buf.len().min(self.pending_input.len());
+            buf[..n].copy_from_slice(&self.pending_input[..n]);
+            self.pending_input.drain(..n);
+            return Ok(n);
+        }
+        read_fd(self.input_fd(), buf)
+    }
+}
+
 // This allows: write!(term, "Hello") instead of write!(term.stdout(), "Hello")
 impl Write for TerminalIO {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.stdout.write(buf)
+        self.output().write(buf)
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        self.stdout.flush()
+        self.output().flush()
+    }
+}
+
+/// The controlling terminal, opened for reading and writing
+struct Tty {
+    input: File,
+    output: BufWriter<File>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(modes: Modes) -> String {
+        let mut out = Vec::new();
+        modes.write_enable(&mut out).unwrap();
+        modes.write_disable(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_probe_rejected_modes_are_never_written() {
+        let mut terminal = TerminalIO::new(true, true).with_focus_events();
+        let (responses, _) =
+            parse_probe_responses(b"\x1b[?2026;1$y\x1b[?1006;0$y\x1b[?2004;4$y\x1b[?62c");
+        terminal.capabilities.apply(&responses);
+
+        let written = written(terminal.modes());
+        for rejected in ["?1006", "?2004", "?1004"] {
+            assert!(!written.contains(rejected), "{written:?}");
+        }
+        // The urxvt encoding is the fallback
+        assert!(written.contains("\x1b[?1015h\x1b[?1000h"));
+        assert!(written.contains("\x1b[?1000l\x1b[?1015l"));
+    }
+
+    #[test]
+    fn test_mouse_mode_codes() {
+        let written = |mouse_mode| {
+            written(
+                TerminalIO::new(true, true)
+                    .with_mouse_mode(mouse_mode)
+                    .modes(),
+            )
+        };
+
+        let off = written(MouseMode::Off);
+        for code in ["?1000", "?1002", "?1003", "?1006", "?1015"] {
+            assert!(!off.contains(code), "{off:?}");
+        }
+        for (mouse_mode, enable, disable) in [
+            (
+                MouseMode::Press,
+                MOUSE_REPORTING_CODE,
+                DISABLE_MOUSE_REPORTING_CODE,
+            ),
+            (
+                MouseMode::Drag,
+                MOUSE_DRAG_REPORTING_CODE,
+                DISABLE_MOUSE_DRAG_REPORTING_CODE,
+            ),
+            (
+                MouseMode::AnyMotion,
+                MOUSE_MOTION_REPORTING_CODE,
+                DISABLE_MOUSE_MOTION_REPORTING_CODE,
+            ),
+        ] {
+            let written = written(mouse_mode);
+            assert!(written.contains(enable), "{written:?}");
+            assert!(written.contains(disable), "{written:?}");
+            // Only the codes of the chosen mode
+            for code in ["?1000", "?1002", "?1003"] {
+                assert_eq!(written.contains(code), enable.contains(code), "{written:?}");
+            }
+        }
     }
 }
@@ -1,4 +1,4 @@
-use crate::input::{InputParser, Parser, XtermParser};
+use crate::input::{GestureDetector, InputParser, Parser, XtermParser};
 
 /// A builder for the `Parser` struct
 ///
@@ -14,6 +14,7 @@ pub struct ParserBuilder {
     buffer_linear_growth_step: usize,
     max_poll_input_buffer: usize,
     initial_poll_input_buffer_size: usize,
+    gestures: Option<GestureDetector>,
 }
 
 impl Default for ParserBuilder {
@@ -27,6 +28,7 @@ impl Default for ParserBuilder {
             buffer_linear_growth_step,
             max_poll_input_buffer,
             initial_poll_input_buffer_size,
+            gestures: None,
         }
     }
 }
@@ -94,6 +96,22 @@ impl ParserBuilder {
         self.parser = input_parser;
         self
     }
+    /// Recognises clicks, double and triple clicks, long presses and drags in the mouse events
+    ///
+    /// They are reported as `Event::Gesture`, after the mouse events they were recognised from.
+    ///
+    /// # Example
+    /// ```rust
+    /// use talos::{ParserBuilder, input::GestureDetector};
+    ///
+    /// let parser = ParserBuilder::default()
+    ///     .with_gestures(GestureDetector::default())
+    ///     .build();
+    /// ```
+    pub fn with_gestures(mut self, gestures: GestureDetector) -> Self {
+        self.gestures = Some(gestures);
+        self
+    }
     /// Builds the parser with the current settings
     ///
     /// # Example
@@ -113,6 +131,7 @@ impl ParserBuilder {
             poll_input_buffer,
             buffer_linear_growth_step: self.buffer_linear_growth_step,
             max_poll_input_buffer: self.max_poll_input_buffer,
+            gestures: self.gestures,
         }
     }
 }
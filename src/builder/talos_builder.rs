@@ -1,9 +1,9 @@
 use crate::{
-    Talos,
-    backend::{TerminalIO, sys::register_signal_handlers},
+    Talos, TerminationPolicy, Viewport,
+    backend::{Backend, Capabilities, ColourDepth, TerminalIO, sys::register_signal_handlers},
     error::TalosResult,
-    input::Parser,
-    render::{CCell, Canvas},
+    input::{KeyboardEnhancement, MouseMode, Parser},
+    render::{CCell, Canvas, CursorShape},
 };
 
 use super::ParserBuilder;
@@ -22,11 +22,21 @@ use super::ParserBuilder;
 /// assert!(talos.is_ok());
 /// ```
 #[must_use]
+#[allow(clippy::struct_excessive_bools)]
 pub struct TalosBuilder {
     hide_cursor: bool,
     alternate_screen: bool,
     set_up_panic_handler: bool,
+    use_tty: bool,
+    probe_capabilities: bool,
+    focus_events: bool,
+    keyboard_enhancement: KeyboardEnhancement,
+    mouse_mode: Option<MouseMode>,
     input_parser: Parser,
+    backend: Option<Box<dyn Backend>>,
+    termination_policy: TerminationPolicy,
+    viewport: Viewport,
+    colour_depth: Option<ColourDepth>,
 }
 
 impl Default for TalosBuilder {
@@ -39,7 +49,16 @@ impl Default for TalosBuilder {
             hide_cursor: true,
             alternate_screen: true,
             set_up_panic_handler: true,
+            use_tty: false,
+            probe_capabilities: true,
+            focus_events: false,
+            keyboard_enhancement: KeyboardEnhancement::default(),
+            mouse_mode: None,
             input_parser,
+            backend: None,
+            termination_policy: TerminationPolicy::default(),
+            viewport: Viewport::default(),
+            colour_depth: None,
         }
     }
 }
@@ -59,6 +78,25 @@ impl TalosBuilder {
         self
     }
 
+    /// Sets the backend to a custom one
+    ///
+    /// If no backend is set, [`TerminalIO`](backend/struct.TerminalIO.html) is used, configured
+    /// with the cursor, alternate screen, viewport, mouse, focus, keyboard and tty settings of
+    /// this builder.
+    /// A custom backend is responsible for its own cursor and screen settings.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::{Talos, backend::TerminalIO};
+    ///
+    /// let backend = TerminalIO::new(false, true);
+    /// let talos = Talos::builder().with_backend(Box::new(backend)).build();
+    /// ```
+    pub fn with_backend(mut self, backend: Box<dyn Backend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
     /// Enables the Terminal cursor
     ///
     /// # Example
@@ -85,6 +123,147 @@ impl TalosBuilder {
         self
     }
 
+    /// Uses the controlling terminal `/dev/tty` for input and output, instead of stdin and stdout
+    ///
+    /// This keeps stdin and stdout of the process free for data, so that e.g.
+    /// `cat data | mytool | tee log` works.
+    /// Has no effect if a custom backend is set.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::Talos;
+    ///
+    /// let talos = Talos::builder().use_tty().build();
+    /// ```
+    pub fn use_tty(mut self) -> Self {
+        self.use_tty = true;
+        self
+    }
+
+    /// Reports focus changes of the terminal window as `Event::FocusGained` and `Event::FocusLost`
+    ///
+    /// Has no effect if a custom backend is set, see `TerminalIO::with_focus_events` instead.
+    /// Terminals without focus reporting (see `Capabilities::focus_events`) never send these events.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::Talos;
+    ///
+    /// let talos = Talos::builder().with_focus_events().build();
+    /// ```
+    pub fn with_focus_events(mut self) -> Self {
+        self.focus_events = true;
+        self
+    }
+
+    /// Sets which mouse events the terminal reports
+    ///
+    /// By default, button presses and releases and the mouse wheel are reported - inline viewports
+    /// report nothing. `MouseMode::Drag` adds movement while a button is held down,
+    /// `MouseMode::AnyMotion` every movement, e.g. for hover highlighting.
+    /// Has no effect if a custom backend is set, see `TerminalIO::with_mouse_mode` instead.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::{Talos, input::MouseMode};
+    ///
+    /// let talos = Talos::builder().with_mouse_mode(MouseMode::AnyMotion).build();
+    /// ```
+    pub fn with_mouse_mode(mut self, mouse_mode: MouseMode) -> Self {
+        self.mouse_mode = Some(mouse_mode);
+        self
+    }
+
+    /// Requests enhancements of the kitty keyboard protocol from the terminal
+    ///
+    /// This reports keys the legacy encoding cannot, like `Ctrl+i` as opposed to `Tab`, the
+    /// super, hyper and meta modifiers, the lock key state and key releases.
+    /// Terminals without support for the protocol (see `Capabilities::kitty_keyboard`) keep
+    /// reporting keys as before.
+    /// Has no effect if a custom backend is set, see `TerminalIO::with_keyboard_enhancement` instead.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::{Talos, input::KeyboardEnhancement};
+    ///
+    /// let talos = Talos::builder()
+    ///     .with_keyboard_enhancement(KeyboardEnhancement {
+    ///         disambiguate_escape_codes: true,
+    ///         ..KeyboardEnhancement::default()
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn with_keyboard_enhancement(mut self, enhancement: KeyboardEnhancement) -> Self {
+        self.keyboard_enhancement = enhancement;
+        self
+    }
+
+    /// Disables querying the terminal for its capabilities
+    ///
+    /// By default, `Talos` asks the terminal what it supports while building, which takes one
+    /// round trip to the terminal. Without probing, the colour depth is guessed from the
+    /// environment and everything else is assumed to be supported.
+    /// See [`Capabilities`](backend/struct.Capabilities.html).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::Talos;
+    ///
+    /// let talos = Talos::builder().without_capability_probe().build();
+    /// ```
+    pub fn without_capability_probe(mut self) -> Self {
+        self.probe_capabilities = false;
+        self
+    }
+
+    /// Sets the colour depth colours are rendered in, instead of detecting it
+    ///
+    /// Colours the terminal cannot display are replaced by the closest ones it can, e.g. RGB
+    /// colours by entries of the 256 colour palette. `ColourDepth::Monochrome` drops all colours.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::{Talos, backend::ColourDepth};
+    ///
+    /// // Stay legible on the Linux console
+    /// let talos = Talos::builder().with_colour_depth(ColourDepth::Ansi16).build();
+    /// ```
+    pub fn with_colour_depth(mut self, colour_depth: ColourDepth) -> Self {
+        self.colour_depth = Some(colour_depth);
+        self
+    }
+
+    /// Sets the area of the terminal to render into
+    ///
+    /// By default, `Talos` renders to the whole terminal.
+    /// An inline viewport renders below the cursor instead, without the alternate screen.
+    /// See [`Viewport`](enum.Viewport.html).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::{Talos, Viewport};
+    ///
+    /// let talos = Talos::builder().with_viewport(Viewport::Inline(5)).build();
+    /// ```
+    pub fn with_viewport(mut self, viewport: Viewport) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    /// Renders into `height` lines below the cursor, instead of the whole terminal
+    ///
+    /// Shorthand for `with_viewport(Viewport::Inline(height))`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::Talos;
+    ///
+    /// let talos = Talos::builder().with_inline_viewport(5).build();
+    /// ```
+    pub fn with_inline_viewport(self, height: u16) -> Self {
+        self.with_viewport(Viewport::Inline(height))
+    }
+
     /// Disables the panic handler hook
     ///
     /// This can lead to unrecoverable panics, and returning the Terminal in a partially configured
@@ -104,6 +283,45 @@ impl TalosBuilder {
         self
     }
 
+    /// Sets what happens when the process is asked to terminate (`SIGTERM` or `SIGINT`)
+    ///
+    /// By default, `Talos` restores the terminal and exits the process with exit code `0`.
+    /// See [`TerminationPolicy`](enum.TerminationPolicy.html) for the alternatives.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+    /// use talos::{Talos, TerminationPolicy};
+    ///
+    /// let running = Arc::new(AtomicBool::new(true));
+    /// let flag = running.clone();
+    /// let talos = Talos::builder()
+    ///     .with_termination_policy(TerminationPolicy::Callback(Box::new(move || {
+    ///         flag.store(false, Ordering::Relaxed);
+    ///     })))
+    ///     .build();
+    /// ```
+    pub fn with_termination_policy(mut self, termination_policy: TerminationPolicy) -> Self {
+        self.termination_policy = termination_policy;
+        self
+    }
+
+    /// Reports `SIGTERM` and `SIGINT` as `Event::Terminate` instead of exiting the process
+    ///
+    /// Shorthand for `with_termination_policy(TerminationPolicy::Event)`.
+    /// The event is returned by `poll_input` once per signal, and the application is responsible for
+    /// shutting down.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::Talos;
+    ///
+    /// let talos = Talos::builder().with_terminate_event().build();
+    /// ```
+    pub fn with_terminate_event(self) -> Self {
+        self.with_termination_policy(TerminationPolicy::Event)
+    }
+
     /// Build the `Talos` instance
     ///
     /// # Errors
@@ -121,9 +339,38 @@ impl TalosBuilder {
         if self.set_up_panic_handler {
             register_signal_handlers()?;
         }
-        // Initialize TerminalIO based on these settings
-        let terminal = TerminalIO::new(self.hide_cursor, self.alternate_screen)?;
-        let (rows, cols) = terminal.size()?;
+        // Initialize TerminalIO based on these settings, if no other backend was supplied
+        let mut backend: Box<dyn Backend> = if let Some(backend) = self.backend {
+            backend
+        } else {
+            let mut terminal = match self.viewport {
+                Viewport::Fullscreen => TerminalIO::new(self.hide_cursor, self.alternate_screen),
+                Viewport::Inline(_) => TerminalIO::new_inline(self.hide_cursor),
+            };
+            if self.focus_events {
+                terminal = terminal.with_focus_events();
+            }
+            terminal = terminal.with_keyboard_enhancement(self.keyboard_enhancement);
+            if let Some(mouse_mode) = self.mouse_mode {
+                terminal = terminal.with_mouse_mode(mouse_mode);
+            }
+            if self.use_tty {
+                Box::new(terminal.with_tty()?)
+            } else {
+                Box::new(terminal)
+            }
+        };
+        backend.enter()?;
+        let mut capabilities = if self.probe_capabilities {
+            backend.probe_capabilities()?
+        } else {
+            Capabilities::from_env()
+        };
+        if let Some(colour_depth) = self.colour_depth {
+            capabilities.colour_depth = colour_depth;
+        }
+        backend.enable_modes()?;
+        let (cols, rows) = self.viewport.size(backend.size()?);
         let thoth = thoth::Thoth::new().map_err(|e| {
             crate::error::TalosError::GenericError(format!("Failed to initialize Thoth: {:?}", e))
         })?;
@@ -134,14 +381,28 @@ impl TalosBuilder {
         //    even be enough!
         let output_buffer = Vec::with_capacity(buffer_size * 10);
 
-        Ok(Talos {
-            terminal,
+        let mut talos = Talos {
+            backend,
             canvas: Canvas::new(cols, rows),
             size: (cols, rows),
+            viewport: self.viewport,
+            capabilities,
+            inline_cursor: None,
+            cursor: None,
+            cursor_shape: CursorShape::Default,
+            applied_cursor_shape: CursorShape::Default,
+            cursor_shown: false,
             thoth,
             previous_buffer,
             output_buffer,
             parser: self.input_parser,
-        })
+            pending_events: Vec::new(),
+            termination_policy: self.termination_policy,
+            terminated: false,
+            suspended: false,
+            resume_on_continue: false,
+        };
+        talos.reserve_viewport()?;
+        Ok(talos)
     }
 }
@@ -12,6 +12,10 @@ pub enum TalosError {
     InvalidArgument(String),
     /// Invalid State of State Machine
     InvalidState,
+    /// The process was asked to terminate (`SIGTERM` or `SIGINT`)
+    ///
+    /// Only returned if the `TerminationPolicy` is `Error`. The terminal has already been restored.
+    Terminated,
     /// Generic error - Only use for development, always refactor to something more specific
     GenericError(String),
 }
@@ -40,6 +44,7 @@ impl std::fmt::Display for TalosError {
             TalosError::IOError(e) => write!(f, "IOError: {e}"),
             TalosError::InvalidArgument(s) => write!(f, "InvalidArgument: {s}"),
             TalosError::InvalidState => write!(f, "InvalidState"),
+            TalosError::Terminated => write!(f, "Terminated"),
             TalosError::GenericError(s) => write!(f, "GenericError: {s}"),
         }
     }
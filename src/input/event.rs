@@ -1,3 +1,5 @@
+use super::Gesture;
+
 /// An input event
 ///
 /// # Example
@@ -12,6 +14,67 @@ pub enum Event {
     KeyEvent(KeyEvent),
     /// A mouse event
     MouseEvent(MouseEvent),
+    /// A click, long press or drag, recognised from mouse events
+    ///
+    /// Only reported if a `GestureDetector` is set up, see `ParserBuilder::with_gestures`.
+    Gesture(Gesture),
+    /// The terminal was resized
+    ///
+    /// The canvas already has the new size when this event is received.
+    Resize {
+        /// The new width of the terminal
+        width: u16,
+        /// The new height of the terminal
+        height: u16,
+    },
+    /// Text was pasted into the terminal
+    ///
+    /// Only reported by terminals supporting bracketed paste - otherwise, pasted text arrives as
+    /// individual key events. Line breaks are normalised to `\n`.
+    /// Pastes longer than the limit of the parser arrive in several parts, see
+    /// `XtermParser::with_max_paste_len`.
+    Paste(String),
+    /// The terminal window gained focus
+    ///
+    /// Only reported if focus events are enabled, see `TalosBuilder::with_focus_events`.
+    FocusGained,
+    /// The terminal window lost focus
+    ///
+    /// Only reported if focus events are enabled, see `TalosBuilder::with_focus_events`.
+    FocusLost,
+    /// The position of the cursor, in answer to `Query::CursorPosition`
+    CursorPosition {
+        /// The column of the cursor, starting at 0
+        column: u16,
+        /// The row of the cursor, starting at 0
+        row: u16,
+    },
+    /// The default foreground colour of the terminal as red, green and blue, in answer to
+    /// `Query::ForegroundColour`
+    ForegroundColour(u8, u8, u8),
+    /// The default background colour of the terminal as red, green and blue, in answer to
+    /// `Query::BackgroundColour`
+    BackgroundColour(u8, u8, u8),
+    /// The size of the text area of the terminal window in pixels, in answer to
+    /// `Query::WindowPixelSize`
+    WindowPixelSize {
+        /// The width in pixels
+        width: u16,
+        /// The height in pixels
+        height: u16,
+    },
+    /// The size of a single cell in pixels, in answer to `Query::CellPixelSize`
+    CellPixelSize {
+        /// The width in pixels
+        width: u16,
+        /// The height in pixels
+        height: u16,
+    },
+    /// The process was asked to terminate (`SIGTERM` or `SIGINT`)
+    ///
+    /// Only emitted if the `TerminationPolicy` is `Event`, e.g. by enabling
+    /// `TalosBuilder::with_terminate_event`.
+    Terminate,
     /// An unknown event, used as a fallback
     Unknown(Vec<u8>), // Useful for debugging weird sequences
 }
@@ -55,14 +118,41 @@ pub enum MouseEventKind {
     Down(MouseButton),
     /// A mouse button was released
     Up(MouseButton),
-    /// A mouse button was dragged
+    /// The mouse was moved with a button held down - only reported with `MouseMode::Drag` or
+    /// `MouseMode::AnyMotion`
     Drag(MouseButton),
-    /// The mouse was moved - Not yet implemented in the Backend `TerminalIO`
+    /// The mouse was moved without a button held down - only reported with `MouseMode::AnyMotion`
     Moved,
     /// The mouse wheel was scrolled upwards
     ScrollUp,
     /// The mouse wheel was scrolled downwards
     ScrollDown,
+    /// The mouse wheel was tilted, or scrolled, to the left
+    ScrollLeft,
+    /// The mouse wheel was tilted, or scrolled, to the right
+    ScrollRight,
+}
+
+/// Which mouse events the terminal reports
+///
+/// # Example
+/// ```rust,no_run
+/// use talos::{Talos, input::MouseMode};
+///
+/// // Hover highlighting needs every movement
+/// let talos = Talos::builder().with_mouse_mode(MouseMode::AnyMotion).build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MouseMode {
+    /// No mouse events - the terminal keeps handling text selection itself
+    Off,
+    /// Presses and releases of the buttons and the mouse wheel (mode `?1000`)
+    #[default]
+    Press,
+    /// Also movement while a button is held down (mode `?1002`)
+    Drag,
+    /// Also movement without a button held down (mode `?1003`)
+    AnyMotion,
 }
 
 /// A mouse button
@@ -73,7 +163,7 @@ pub enum MouseEventKind {
 ///
 /// let button = MouseButton::Left;
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MouseButton {
     /// The left mouse button
     Left,
@@ -81,6 +171,12 @@ pub enum MouseButton {
     Middle,
     /// The right mouse button
     Right,
+    /// The back (side) button - button 8
+    Back,
+    /// The forward (side) button - button 9
+    Forward,
+    /// Any other button, by its number - buttons 10 and 11
+    Other(u8),
 }
 
 /// A key event
@@ -97,6 +193,14 @@ pub struct KeyEvent {
     pub code: KeyCode,
     /// The modifiers that were active when the key event occurred
     pub modifiers: KeyModifiers,
+    /// Whether the key was pressed, repeated or released
+    ///
+    /// Always `KeyEventKind::Press`, unless `KeyboardEnhancement::report_event_types` is enabled.
+    pub kind: KeyEventKind,
+    /// The state of the lock keys
+    ///
+    /// Only reported by terminals supporting the kitty keyboard protocol.
+    pub state: KeyEventState,
 }
 
 impl KeyEvent {
@@ -114,7 +218,89 @@ impl KeyEvent {
     /// ```
     #[must_use]
     pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
-        Self { code, modifiers }
+        Self {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::default(),
+        }
+    }
+}
+
+/// Whether a key was pressed, repeated or released
+///
+/// # Example
+/// ```rust
+/// use talos::input::{KeyEvent, KeyEventKind, KeyCode, KeyModifiers};
+///
+/// let event = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::default());
+/// assert_eq!(event.kind, KeyEventKind::Press);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyEventKind {
+    /// The key was pressed
+    #[default]
+    Press,
+    /// The key is held down and repeats
+    Repeat,
+    /// The key was released
+    Release,
+}
+
+/// The state of the lock keys when a key event occurred
+///
+/// # Example
+/// ```rust
+/// use talos::input::KeyEventState;
+///
+/// let state = KeyEventState::default();
+/// assert!(!state.caps_lock);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct KeyEventState {
+    /// Caps lock is on
+    pub caps_lock: bool,
+    /// Num lock is on
+    pub num_lock: bool,
+}
+
+/// The enhancements of the kitty keyboard protocol to request from the terminal
+///
+/// Terminals that do not support the protocol ignore the request and keep sending legacy
+/// sequences - see `Capabilities::kitty_keyboard`.
+///
+/// # Example
+/// ```rust,no_run
+/// use talos::{Talos, input::KeyboardEnhancement};
+///
+/// let talos = Talos::builder()
+///     .with_keyboard_enhancement(KeyboardEnhancement {
+///         disambiguate_escape_codes: true,
+///         report_event_types: true,
+///         ..KeyboardEnhancement::default()
+///     })
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct KeyboardEnhancement {
+    /// Report keys that are ambiguous otherwise, e.g. `Esc`, `Ctrl+i` (`Tab`) or `Alt+key`
+    pub disambiguate_escape_codes: bool,
+    /// Report key repeats and releases, see `KeyEventKind`
+    pub report_event_types: bool,
+    /// Report the shifted key as well, e.g. `:` instead of `;` for `Shift+;`
+    pub report_alternate_keys: bool,
+    /// Report all keys as escape sequences, including text, `Enter`, `Tab` and `Backspace`
+    pub report_all_keys: bool,
+}
+
+impl KeyboardEnhancement {
+    /// The progressive enhancement flags of the kitty keyboard protocol
+    pub(crate) fn flags(self) -> u8 {
+        u8::from(self.disambiguate_escape_codes)
+            | u8::from(self.report_event_types) << 1
+            | u8::from(self.report_alternate_keys) << 2
+            | u8::from(self.report_all_keys) << 3
     }
 }
 
@@ -143,8 +329,14 @@ pub enum KeyCode {
     PageDown,
     Delete,
     Insert,
-    F(u8), // F1 - F12
+    F(u8), // F1 - F35
     Char(char),
+    Menu,
+    PrintScreen,
+    Pause,
+    Keypad(char), // Digits and operators of the keypad in application mode
+    KeypadEnter,
+    KeypadBegin, // The center key of the keypad - '5' with num lock off
 }
 
 /// The modifiers that were active when the key event occurred
@@ -154,10 +346,9 @@ pub enum KeyCode {
 /// use talos::input::KeyModifiers;
 ///
 /// let modifiers = KeyModifiers {
-///    none: true,
-///    shift: false,
-///    ctrl: false,
-///    alt: false,
+///    shift: true,
+///    none: false,
+///    ..KeyModifiers::default()
 /// };
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -171,6 +362,12 @@ pub struct KeyModifiers {
     pub ctrl: bool,
     /// The alt key
     pub alt: bool,
+    /// The super key (Windows / Command) - only reported with the kitty keyboard protocol
+    pub super_key: bool,
+    /// The hyper key - only reported with the kitty keyboard protocol
+    pub hyper: bool,
+    /// The meta key - only reported with the kitty keyboard protocol
+    pub meta: bool,
 }
 
 impl Default for KeyModifiers {
@@ -180,6 +377,9 @@ impl Default for KeyModifiers {
             shift: false,
             ctrl: false,
             alt: false,
+            super_key: false,
+            hyper: false,
+            meta: false,
         }
     }
 }
@@ -0,0 +1,387 @@
+use std::time::{Duration, Instant};
+
+use super::{Event, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+/// A gesture, synthesised from raw mouse events by a `GestureDetector`
+///
+/// # Example
+/// ```rust
+/// use talos::input::{Event, Gesture, GestureKind};
+///
+/// fn handle(event: &Event) {
+///     if let Event::Gesture(Gesture { kind: GestureKind::Click { count: 2 }, column, row, .. }) = event {
+///         // Open the item at column, row
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gesture {
+    /// The kind of gesture
+    pub kind: GestureKind,
+    /// The button the gesture was made with
+    pub button: MouseButton,
+    /// The column where the gesture occurred - for drags, the current position
+    pub column: u16,
+    /// The row where the gesture occurred - for drags, the current position
+    pub row: u16,
+    /// The modifiers that were active when the gesture occurred
+    pub modifiers: KeyModifiers,
+}
+
+/// The kind of gesture
+///
+/// # Example
+/// ```rust
+/// use talos::input::GestureKind;
+///
+/// let double_click = GestureKind::Click { count: 2 };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GestureKind {
+    /// A button was pressed and released without moving
+    ///
+    /// `count` is 2 for double and 3 for triple clicks, after which it starts over at 1.
+    Click {
+        /// The number of clicks in quick succession
+        count: u8,
+    },
+    /// A button was held down without moving - no click follows when it is released
+    LongPress,
+    /// The mouse started moving while a button is held down
+    DragStart {
+        /// The `(column, row)` where the button was pressed
+        origin: (u16, u16),
+    },
+    /// The mouse moved further while a button is held down
+    DragMove {
+        /// The `(column, row)` where the button was pressed
+        origin: (u16, u16),
+    },
+    /// The button was released, ending the drag
+    DragEnd {
+        /// The `(column, row)` where the button was pressed
+        origin: (u16, u16),
+    },
+}
+
+/// Turns raw mouse events into gestures - clicks, double and triple clicks, long presses and drags
+///
+/// The recognised gestures are appended as `Event::Gesture` after the raw mouse events, which are
+/// left in place. Drags are only reported with `MouseMode::Drag` or `MouseMode::AnyMotion`.
+///
+/// Set it up with `ParserBuilder::with_gestures` to have `Talos::poll_input` report gestures, or
+/// call `process` on events from any other source.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use talos::{ParserBuilder, Talos, input::GestureDetector};
+///
+/// let gestures = GestureDetector::default().with_multi_click_interval(Duration::from_millis(300));
+/// let parser = ParserBuilder::default().with_gestures(gestures).build();
+/// let talos = Talos::builder().with_input_parser(parser).build();
+/// ```
+#[must_use]
+#[derive(Debug, Clone)]
+pub struct GestureDetector {
+    multi_click_interval: Duration,
+    long_press: Duration,
+    drag_threshold: u16,
+    pressed: Option<Press>,
+    last_click: Option<LastClick>,
+}
+
+impl Default for GestureDetector {
+    fn default() -> Self {
+        Self {
+            multi_click_interval: Duration::from_millis(500),
+            long_press: Duration::from_millis(800),
+            drag_threshold: 1,
+            pressed: None,
+            last_click: None,
+        }
+    }
+}
+
+/// The button currently held down
+#[derive(Debug, Clone)]
+struct Press {
+    event: MouseEvent,
+    button: MouseButton,
+    at: Instant,
+    dragging: bool,
+    long_pressed: bool,
+}
+
+#[derive(Debug, Clone)]
+struct LastClick {
+    button: MouseButton,
+    position: (u16, u16),
+    at: Instant,
+    count: u8,
+}
+
+impl GestureDetector {
+    /// Sets how quickly clicks have to follow each other to count as double or triple clicks
+    ///
+    /// The default is 500ms.
+    pub fn with_multi_click_interval(mut self, interval: Duration) -> Self {
+        self.multi_click_interval = interval;
+        self
+    }
+
+    /// Sets how long a button has to be held down to count as a long press
+    ///
+    /// The default is 800ms.
+    pub fn with_long_press(mut self, duration: Duration) -> Self {
+        self.long_press = duration;
+        self
+    }
+
+    /// Sets how many cells the mouse has to move while a button is held down to start a drag
+    ///
+    /// The default is 1 - every movement starts a drag.
+    pub fn with_drag_threshold(mut self, cells: u16) -> Self {
+        self.drag_threshold = cells.max(1);
+        self
+    }
+
+    /// Appends the gestures recognised in `events` to `events`
+    ///
+    /// Call this with every batch of events, even empty ones - long presses are recognised by
+    /// time passing.
+    pub fn process(&mut self, events: &mut Vec<Event>) {
+        self.process_at(events, Instant::now());
+    }
+
+    /// Returns how long until a long press is recognised, if a button is held down
+    ///
+    /// `Talos::wait_input` waits at most this long.
+    #[must_use]
+    pub fn timeout(&self) -> Option<Duration> {
+        let press = self.pressed.as_ref()?;
+        if press.dragging || press.long_pressed {
+            return None;
+        }
+        Some(self.long_press.saturating_sub(press.at.elapsed()))
+    }
+
+    fn process_at(&mut self, events: &mut Vec<Event>, now: Instant) {
+        let mut gestures = Vec::new();
+        for event in events.iter() {
+            if let Event::MouseEvent(mouse) = event {
+                self.check_long_press(now, &mut gestures);
+                self.handle_mouse(mouse, now, &mut gestures);
+            }
+        }
+        self.check_long_press(now, &mut gestures);
+        events.extend(gestures);
+    }
+
+    fn check_long_press(&mut self, now: Instant, gestures: &mut Vec<Event>) {
+        let Some(press) = &mut self.pressed else {
+            return;
+        };
+        if press.dragging || press.long_pressed || now.duration_since(press.at) < self.long_press {
+            return;
+        }
+        press.long_pressed = true;
+        gestures.push(gesture(GestureKind::LongPress, press.button, &press.event));
+    }
+
+    fn handle_mouse(&mut self, mouse: &MouseEvent, now: Instant, gestures: &mut Vec<Event>) {
+        match mouse.kind {
+            MouseEventKind::Down(button) => {
+                self.pressed = Some(Press {
+                    event: mouse.clone(),
+                    button,
+                    at: now,
+                    dragging: false,
+                    long_pressed: false,
+                });
+            }
+            MouseEventKind::Drag(button) => {
+                let Some(press) = &mut self.pressed else {
+                    return;
+                };
+                if press.button != button {
+                    return;
+                }
+                let origin = (press.event.column, press.event.row);
+                if press.dragging {
+                    gestures.push(gesture(GestureKind::DragMove { origin }, button, mouse));
+                } else if distance(origin, (mouse.column, mouse.row)) >= self.drag_threshold {
+                    press.dragging = true;
+                    gestures.push(gesture(GestureKind::DragStart { origin }, button, mouse));
+                }
+            }
+            MouseEventKind::Up(button) => {
+                // The release of another button leaves the press alone
+                let Some(press) = self.pressed.take_if(|press| press.button == button) else {
+                    return;
+                };
+                let origin = (press.event.column, press.event.row);
+                if press.dragging {
+                    gestures.push(gesture(GestureKind::DragEnd { origin }, button, mouse));
+                } else if !press.long_pressed {
+                    let count = self.click_count(button, origin, now);
+                    gestures.push(gesture(GestureKind::Click { count }, button, mouse));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Counts the click, together with the clicks just before it
+    fn click_count(&mut self, button: MouseButton, position: (u16, u16), now: Instant) -> u8 {
+        let count = match &self.last_click {
+            Some(last)
+                if last.button == button
+                    && last.position == position
+                    && last.count < 3
+                    && now.duration_since(last.at) <= self.multi_click_interval =>
+            {
+                last.count + 1
+            }
+            _ => 1,
+        };
+        self.last_click = Some(LastClick {
+            button,
+            position,
+            at: now,
+            count,
+        });
+        count
+    }
+}
+
+fn distance(a: (u16, u16), b: (u16, u16)) -> u16 {
+    a.0.abs_diff(b.0).max(a.1.abs_diff(b.1))
+}
+
+fn gesture(kind: GestureKind, button: MouseButton, mouse: &MouseEvent) -> Event {
+    Event::Gesture(Gesture {
+        kind,
+        button,
+        column: mouse.column,
+        row: mouse.row,
+        modifiers: mouse.modifiers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> Event {
+        Event::MouseEvent(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::default(),
+        })
+    }
+
+    fn gestures(events: &[Event]) -> Vec<GestureKind> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Gesture(gesture) => Some(gesture.kind),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_clicks() {
+        let mut detector = GestureDetector::default();
+        let start = Instant::now();
+        let left = MouseButton::Left;
+
+        let mut counts = Vec::new();
+        for i in 0..4 {
+            let mut events = vec![
+                mouse(MouseEventKind::Down(left), 3, 4),
+                mouse(MouseEventKind::Up(left), 3, 4),
+            ];
+            detector.process_at(&mut events, start + Duration::from_millis(100 * i));
+            assert_eq!(events.len(), 3, "The raw events are kept");
+            counts.extend(gestures(&events));
+        }
+        // Too slow for a double click
+        let mut events = vec![
+            mouse(MouseEventKind::Down(left), 3, 4),
+            mouse(MouseEventKind::Up(left), 3, 4),
+        ];
+        detector.process_at(&mut events, start + Duration::from_secs(2));
+        counts.extend(gestures(&events));
+
+        let click = |count| GestureKind::Click { count };
+        assert_eq!(
+            counts,
+            vec![click(1), click(2), click(3), click(1), click(1)]
+        );
+    }
+
+    #[test]
+    fn test_interleaved_buttons() {
+        let mut detector = GestureDetector::default();
+        let (left, right) = (MouseButton::Left, MouseButton::Right);
+
+        let mut events = vec![
+            mouse(MouseEventKind::Down(left), 3, 4),
+            mouse(MouseEventKind::Up(right), 3, 4),
+            mouse(MouseEventKind::Up(left), 3, 4),
+            mouse(MouseEventKind::Down(left), 5, 4),
+            mouse(MouseEventKind::Down(right), 5, 4),
+            mouse(MouseEventKind::Up(left), 5, 4),
+            mouse(MouseEventKind::Up(right), 5, 4),
+        ];
+        detector.process_at(&mut events, Instant::now());
+
+        let clicks: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Gesture(gesture) => Some((gesture.kind, gesture.button)),
+                _ => None,
+            })
+            .collect();
+        let click = GestureKind::Click { count: 1 };
+        assert_eq!(clicks, vec![(click, left), (click, right)]);
+    }
+
+    #[test]
+    fn test_drag_and_long_press() {
+        let mut detector = GestureDetector::default();
+        let start = Instant::now();
+        let right = MouseButton::Right;
+
+        let mut events = vec![
+            mouse(MouseEventKind::Down(right), 1, 1),
+            mouse(MouseEventKind::Drag(right), 2, 1),
+            mouse(MouseEventKind::Drag(right), 5, 3),
+            mouse(MouseEventKind::Up(right), 5, 3),
+        ];
+        detector.process_at(&mut events, start);
+        let origin = (1, 1);
+        assert_eq!(
+            gestures(&events),
+            vec![
+                GestureKind::DragStart { origin },
+                GestureKind::DragMove { origin },
+                GestureKind::DragEnd { origin },
+            ]
+        );
+
+        // Held down without moving - a long press instead of a click
+        let mut events = vec![mouse(MouseEventKind::Down(right), 7, 7)];
+        detector.process_at(&mut events, start);
+        assert!(gestures(&events).is_empty());
+        let mut events = Vec::new();
+        detector.process_at(&mut events, start + Duration::from_secs(1));
+        assert_eq!(gestures(&events), vec![GestureKind::LongPress]);
+        let mut events = vec![mouse(MouseEventKind::Up(right), 7, 7)];
+        detector.process_at(&mut events, start + Duration::from_secs(2));
+        assert!(gestures(&events).is_empty());
+    }
+}
@@ -0,0 +1,783 @@
+use std::{
+    fmt,
+    path::Path,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::error::{TalosError, TalosResult};
+
+use super::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+/// A key together with its modifiers, e.g. `Ctrl+x`
+///
+/// Parsed from and displayed as text like `ctrl+x`, `alt+shift+tab`, `F5` or `space`.
+/// Letters are matched by the character they produce, so `A` is the same as `shift+a`.
+/// `plus`, `equals` and `hash` name the `+`, `=` and `#` keys.
+///
+/// # Example
+/// ```rust
+/// use talos::input::{KeyCode, KeyCombo, KeyEvent, KeyModifiers};
+///
+/// let combo: KeyCombo = "ctrl+x".parse().unwrap();
+/// assert_eq!(combo.to_string(), "Ctrl+x");
+///
+/// let modifiers = KeyModifiers { ctrl: true, none: false, ..KeyModifiers::default() };
+/// assert!(combo.matches(&KeyEvent::new(KeyCode::Char('x'), modifiers)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyCombo {
+    /// The key
+    pub code: KeyCode,
+    /// The modifiers held down
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyCombo {
+    /// Creates a new key combo
+    ///
+    /// Shift is dropped for characters - the character itself tells whether shift was held down.
+    #[must_use]
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers = modifiers;
+        if let KeyCode::Char(_) = code {
+            modifiers.shift = false;
+        }
+        modifiers.none = !(modifiers.shift
+            || modifiers.ctrl
+            || modifiers.alt
+            || modifiers.super_key
+            || modifiers.hyper
+            || modifiers.meta);
+        Self { code, modifiers }
+    }
+
+    /// Returns whether `key` is this key combo
+    #[must_use]
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        *self == KeyCombo::from(key)
+    }
+}
+
+impl From<&KeyEvent> for KeyCombo {
+    fn from(key: &KeyEvent) -> Self {
+        KeyCombo::new(key.code, key.modifiers)
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = TalosError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || TalosError::InvalidArgument(format!("Invalid key combo: '{s}'"));
+
+        // The last part is the key - which may be a '+' itself
+        let (modifier_part, key) = if s == "+" {
+            ("", "+")
+        } else if let Some(modifier_part) = s.strip_suffix("++") {
+            (modifier_part, "+")
+        } else {
+            s.rsplit_once('+').unwrap_or(("", s))
+        };
+
+        let mut modifiers = KeyModifiers::default();
+        for modifier in modifier_part.split('+').filter(|m| !m.is_empty()) {
+            match modifier.to_lowercase().as_str() {
+                "shift" => modifiers.shift = true,
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "alt" => modifiers.alt = true,
+                "super" => modifiers.super_key = true,
+                "hyper" => modifiers.hyper = true,
+                "meta" => modifiers.meta = true,
+                _ => return Err(invalid()),
+            }
+        }
+
+        let code = parse_key_code(key).ok_or_else(invalid)?;
+        // Shift+a is written as A
+        let code = match code {
+            KeyCode::Char(c) if modifiers.shift => KeyCode::Char(c.to_ascii_uppercase()),
+            code => code,
+        };
+        Ok(KeyCombo::new(code, modifiers))
+    }
+}
+
+fn parse_key_code(key: &str) -> Option<KeyCode> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    let key = key.to_lowercase();
+    let code = match key.as_str() {
+        "esc" | "escape" => KeyCode::Esc,
+        "enter" | "return" => KeyCode::Enter,
+        "backspace" => KeyCode::Backspace,
+        "tab" => KeyCode::Tab,
+        "space" => KeyCode::Char(' '),
+        "plus" => KeyCode::Char('+'),
+        "equals" => KeyCode::Char('='),
+        "hash" => KeyCode::Char('#'),
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" | "ins" => KeyCode::Insert,
+        "menu" => KeyCode::Menu,
+        "printscreen" => KeyCode::PrintScreen,
+        "pause" => KeyCode::Pause,
+        "kpenter" => KeyCode::KeypadEnter,
+        "kpbegin" => KeyCode::KeypadBegin,
+        _ => {
+            if let Some(n) = key.strip_prefix('f') {
+                return n
+                    .parse()
+                    .ok()
+                    .filter(|n| (1..=35).contains(n))
+                    .map(KeyCode::F);
+            }
+            let mut chars = key.strip_prefix("kp")?.chars();
+            let (Some(c), None) = (chars.next(), chars.next()) else {
+                return None;
+            };
+            KeyCode::Keypad(c)
+        }
+    };
+    Some(code)
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifiers = [
+            (self.modifiers.ctrl, "Ctrl"),
+            (self.modifiers.alt, "Alt"),
+            (self.modifiers.shift, "Shift"),
+            (self.modifiers.super_key, "Super"),
+            (self.modifiers.hyper, "Hyper"),
+            (self.modifiers.meta, "Meta"),
+        ];
+        for (_, name) in modifiers.iter().filter(|(held, _)| *held) {
+            write!(f, "{name}+")?;
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            KeyCode::Keypad(c) => write!(f, "Kp{c}"),
+            KeyCode::KeypadEnter => write!(f, "KpEnter"),
+            KeyCode::KeypadBegin => write!(f, "KpBegin"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Parses a sequence of key combos, separated by whitespace - e.g. `g g` or `ctrl+x ctrl+s`
+fn parse_sequence(keys: &str) -> TalosResult<Vec<KeyCombo>> {
+    let sequence = keys
+        .split_whitespace()
+        .map(KeyCombo::from_str)
+        .collect::<TalosResult<Vec<_>>>()?;
+    if sequence.is_empty() {
+        return Err(TalosError::InvalidArgument(
+            "Empty key sequence".to_string(),
+        ));
+    }
+    Ok(sequence)
+}
+
+/// A key sequence bound to an action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding<A> {
+    /// The keys to press, one after the other
+    pub keys: Vec<KeyCombo>,
+    /// The action bound to the keys
+    pub action: A,
+}
+
+/// The result of handing a key to a `Keymap`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeymapMatch<'a, A> {
+    /// The keys pressed so far are bound to this action
+    Action(&'a A),
+    /// The keys pressed so far start a longer sequence - wait for the next key
+    Pending,
+    /// The key is not bound - handle it yourself, e.g. as text input
+    Unbound,
+    /// The key broke off the pending sequence, whose keys are bound on their own
+    ///
+    /// Run `completed` first, then handle `then` - what the key itself matched.
+    Interrupted {
+        /// The action bound to the keys pressed before
+        completed: &'a A,
+        /// The match of the key on its own - never `Interrupted`
+        then: Box<KeymapMatch<'a, A>>,
+    },
+}
+
+impl<'a, A> KeymapMatch<'a, A> {
+    /// Returns the actions to run, in order
+    ///
+    /// # Example
+    /// ```rust
+    /// use talos::input::KeymapMatch;
+    ///
+    /// let action = KeymapMatch::Action(&"quit");
+    /// assert_eq!(action.actions(), vec![&"quit"]);
+    /// assert!(KeymapMatch::<&str>::Pending.actions().is_empty());
+    /// ```
+    #[must_use]
+    pub fn actions(&self) -> Vec<&'a A> {
+        match self {
+            KeymapMatch::Action(action) => vec![*action],
+            KeymapMatch::Pending | KeymapMatch::Unbound => Vec::new(),
+            KeymapMatch::Interrupted { completed, then } => {
+                let mut actions = vec![*completed];
+                actions.extend(then.actions());
+                actions
+            }
+        }
+    }
+}
+
+/// Binds keys and key sequences to actions
+///
+/// Bindings belong to a mode, e.g. `"normal"` and `"insert"` for a modal editor. The bindings of
+/// the current mode are active together with those of the `GLOBAL` mode, and of the layers pushed
+/// on top, e.g. for a popup. The topmost binding wins.
+///
+/// Sequences like `g g` wait for the next key. If a shorter sequence is bound as well, it is
+/// triggered by `flush` once the timeout elapsed without another key, or together with the next
+/// key if that does not continue the sequence - see `KeymapMatch::Interrupted`.
+///
+/// # Example
+/// ```rust,no_run
+/// use talos::{Talos, input::Keymap};
+///
+/// let mut keymap = Keymap::new();
+/// keymap.bind(Keymap::<&str>::GLOBAL, "q", "quit").unwrap();
+/// keymap.bind(Keymap::<&str>::GLOBAL, "g g", "top").unwrap();
+/// keymap.bind(Keymap::<&str>::GLOBAL, "ctrl+x ctrl+s", "save").unwrap();
+///
+/// let mut talos = Talos::builder().build().unwrap();
+/// loop {
+///     let mut actions = Vec::new();
+///     if let Some(events) = talos.wait_input(keymap.timeout()).unwrap() {
+///         for event in events {
+///             actions.extend(keymap.handle_event(event).actions());
+///         }
+///     }
+///     actions.extend(keymap.flush());
+///     if actions.contains(&"quit") {
+///         break;
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Keymap<A> {
+    /// The bindings of each mode, in the order the modes were created
+    modes: Vec<(String, Vec<Binding<A>>)>,
+    mode: String,
+    layers: Vec<String>,
+    pending: Vec<KeyCombo>,
+    last_key: Instant,
+    timeout: Duration,
+}
+
+impl<A> Default for Keymap<A> {
+    fn default() -> Self {
+        Self {
+            modes: Vec::new(),
+            mode: Self::GLOBAL.to_string(),
+            layers: Vec::new(),
+            pending: Vec::new(),
+            last_key: Instant::now(),
+            timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Where a binding lives - `(mode index, binding index)`
+type BindingIndex = (usize, usize);
+
+impl<A> Keymap<A> {
+    /// The mode whose bindings are always active
+    pub const GLOBAL: &'static str = "global";
+
+    /// Creates an empty keymap, in the `GLOBAL` mode
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how long to wait for the next key of a sequence
+    ///
+    /// The default is one second.
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Binds a key sequence to `action` in `mode`
+    ///
+    /// The keys are written like `ctrl+s`, sequences separated by whitespace like `g g`.
+    /// Binding the same keys again replaces the previous action.
+    ///
+    /// # Errors
+    /// Returns an error if the keys cannot be parsed.
+    pub fn bind(&mut self, mode: &str, keys: &str, action: A) -> TalosResult<()> {
+        let keys = parse_sequence(keys)?;
+        let mode_index = if let Some(index) = self.modes.iter().position(|(name, _)| name == mode) {
+            index
+        } else {
+            self.modes.push((mode.to_string(), Vec::new()));
+            self.modes.len() - 1
+        };
+        let bindings = &mut self.modes[mode_index].1;
+        bindings.retain(|binding| binding.keys != keys);
+        bindings.push(Binding { keys, action });
+        Ok(())
+    }
+
+    /// Switches to `mode` - the bindings of the previous mode are no longer active
+    pub fn set_mode(&mut self, mode: &str) {
+        mode.clone_into(&mut self.mode);
+        self.pending.clear();
+    }
+
+    /// Returns the current mode
+    #[must_use]
+    pub fn mode(&self) -> &str {
+        &self.mode
+    }
+
+    /// Activates the bindings of `layer` on top of everything else, until `pop_layer`
+    pub fn push_layer(&mut self, layer: &str) {
+        self.layers.push(layer.to_string());
+        self.pending.clear();
+    }
+
+    /// Deactivates the topmost layer and returns it
+    pub fn pop_layer(&mut self) -> Option<String> {
+        self.pending.clear();
+        self.layers.pop()
+    }
+
+    /// Returns the bindings active in the current mode and layers, topmost first
+    ///
+    /// Bindings hidden by a binding of the same keys further up are left out.
+    #[must_use]
+    pub fn active_bindings(&self) -> Vec<&Binding<A>> {
+        let mut active: Vec<&Binding<A>> = Vec::new();
+        for mode in self.active_modes() {
+            for binding in &self.modes[mode].1 {
+                if !active.iter().any(|shown| shown.keys == binding.keys) {
+                    active.push(binding);
+                }
+            }
+        }
+        active
+    }
+
+    /// Returns the keys of the sequence pressed so far, e.g. to show them in a status bar
+    #[must_use]
+    pub fn pending(&self) -> &[KeyCombo] {
+        &self.pending
+    }
+
+    /// Returns how long until `flush` completes the pending sequence, if any
+    ///
+    /// Pass it to `Talos::wait_input` to call `flush` in time.
+    #[must_use]
+    pub fn timeout(&self) -> Option<Duration> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        Some(self.timeout.saturating_sub(self.last_key.elapsed()))
+    }
+
+    /// Handles a key press, returning the action bound to the keys pressed so far
+    ///
+    /// Key releases are ignored.
+    pub fn handle(&mut self, key: &KeyEvent) -> KeymapMatch<'_, A> {
+        self.handle_at(key, Instant::now())
+    }
+
+    /// Handles an event - everything but key presses is `KeymapMatch::Unbound`
+    pub fn handle_event(&mut self, event: &Event) -> KeymapMatch<'_, A> {
+        match event {
+            Event::KeyEvent(key) => self.handle(key),
+            _ => KeymapMatch::Unbound,
+        }
+    }
+
+    /// Completes the pending sequence once the timeout elapsed
+    ///
+    /// Returns the action bound to the keys pressed so far, if there is one. Call this whenever
+    /// no key arrived, e.g. after every `Talos::wait_input`.
+    pub fn flush(&mut self) -> Option<&A> {
+        self.flush_at(Instant::now())
+    }
+
+    fn handle_at(&mut self, key: &KeyEvent, now: Instant) -> KeymapMatch<'_, A> {
+        if key.kind == KeyEventKind::Release {
+            return KeymapMatch::Unbound;
+        }
+        // The sequence was abandoned
+        if now.duration_since(self.last_key) > self.timeout {
+            self.pending.clear();
+        }
+        self.last_key = now;
+
+        let combo = KeyCombo::from(key);
+        self.pending.push(combo);
+        let (mut exact, mut longer) = self.lookup(&self.pending);
+        let mut completed = None;
+        // Not part of the sequence after all - the keys before may be bound on their own, and the
+        // key as well
+        if exact.is_none() && !longer && self.pending.len() > 1 {
+            self.pending.pop();
+            (completed, _) = self.lookup(&self.pending);
+            self.pending.clear();
+            self.pending.push(combo);
+            (exact, longer) = self.lookup(&self.pending);
+        }
+
+        let then = if longer {
+            KeymapMatch::Pending
+        } else {
+            self.pending.clear();
+            match exact {
+                Some((mode, binding)) => KeymapMatch::Action(&self.modes[mode].1[binding].action),
+                None => KeymapMatch::Unbound,
+            }
+        };
+        match completed {
+            Some((mode, binding)) => KeymapMatch::Interrupted {
+                completed: &self.modes[mode].1[binding].action,
+                then: Box::new(then),
+            },
+            None => then,
+        }
+    }
+
+    fn flush_at(&mut self, now: Instant) -> Option<&A> {
+        if self.pending.is_empty() || now.duration_since(self.last_key) < self.timeout {
+            return None;
+        }
+        let (exact, _) = self.lookup(&self.pending);
+        self.pending.clear();
+        exact.map(|(mode, binding)| &self.modes[mode].1[binding].action)
+    }
+
+    /// Returns the indices of the active modes, topmost first
+    fn active_modes(&self) -> Vec<usize> {
+        let names = self
+            .layers
+            .iter()
+            .rev()
+            .chain(std::iter::once(&self.mode))
+            .map(String::as_str)
+            .chain(std::iter::once(Self::GLOBAL));
+        let mut active = Vec::new();
+        for name in names {
+            if let Some(index) = self.modes.iter().position(|(mode, _)| mode == name)
+                && !active.contains(&index)
+            {
+                active.push(index);
+            }
+        }
+        active
+    }
+
+    /// Finds the topmost binding of exactly `keys`, and whether a longer sequence starts with them
+    fn lookup(&self, keys: &[KeyCombo]) -> (Option<BindingIndex>, bool) {
+        let mut exact = None;
+        let mut longer = false;
+        for mode in self.active_modes() {
+            for (index, binding) in self.modes[mode].1.iter().enumerate() {
+                if binding.keys == keys {
+                    exact = exact.or(Some((mode, index)));
+                } else if binding.keys.starts_with(keys) {
+                    longer = true;
+                }
+            }
+        }
+        (exact, longer)
+    }
+}
+
+impl<A: FromStr> Keymap<A> {
+    /// Parses a keymap from a config
+    ///
+    /// Each line binds keys to an action, separated by `=`. The action is parsed with `FromStr`.
+    /// Bindings belong to the mode of the `[mode]` header above them, or to the `GLOBAL` mode.
+    /// Lines starting with `#` are comments. The `=` and `#` keys are written as `equals` and
+    /// `hash` in keys.
+    ///
+    /// ```text
+    /// # Available everywhere
+    /// ctrl+c = quit
+    /// ctrl+equals = zoom_in
+    /// hash = toggle_comment
+    ///
+    /// [normal]
+    /// g g = top
+    /// ctrl+x ctrl+s = save
+    /// i = insert_mode
+    ///
+    /// [insert]
+    /// esc = normal_mode
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error naming the line if a line cannot be parsed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use talos::input::Keymap;
+    ///
+    /// let keymap: Keymap<String> = Keymap::from_config("q = quit\n[normal]\ng g = top").unwrap();
+    /// ```
+    pub fn from_config(config: &str) -> TalosResult<Self> {
+        let mut keymap = Self::new();
+        let mut mode = Self::GLOBAL.to_string();
+
+        for (index, line) in config.lines().enumerate() {
+            let line_error =
+                |e: String| TalosError::InvalidArgument(format!("Keymap line {}: {e}", index + 1));
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[') {
+                let name = header
+                    .strip_suffix(']')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .ok_or_else(|| line_error(format!("Invalid mode header '{line}'")))?;
+                name.clone_into(&mut mode);
+                continue;
+            }
+
+            let (keys, action) = line
+                .split_once('=')
+                .ok_or_else(|| line_error(format!("Expected 'keys = action', found '{line}'")))?;
+            let action = action.trim();
+            let action = action
+                .parse()
+                .map_err(|_| line_error(format!("Invalid action '{action}'")))?;
+            keymap
+                .bind(&mode, keys, action)
+                .map_err(|e| line_error(e.to_string()))?;
+        }
+        Ok(keymap)
+    }
+
+    /// Loads a keymap from a config file - see `from_config` for the format
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::input::Keymap;
+    ///
+    /// let keymap: Keymap<String> = Keymap::load("keys.conf").unwrap();
+    /// ```
+    pub fn load<P: AsRef<Path>>(path: P) -> TalosResult<Self> {
+        Self::from_config(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> KeyEvent {
+        let combo: KeyCombo = s.parse().unwrap();
+        KeyEvent::new(combo.code, combo.modifiers)
+    }
+
+    #[test]
+    fn test_parse_key_combos() {
+        for (text, display) in [
+            ("ctrl+x", "Ctrl+x"),
+            ("Alt+Shift+Tab", "Alt+Shift+Tab"),
+            ("shift+a", "A"),
+            ("f12", "F12"),
+            ("space", "Space"),
+            ("ctrl++", "Ctrl++"),
+            ("kp7", "Kp7"),
+            ("esc", "Esc"),
+            ("ctrl+equals", "Ctrl+="),
+            ("hash", "#"),
+        ] {
+            let combo: KeyCombo = text.parse().unwrap();
+            assert_eq!(combo.to_string(), display);
+            assert_eq!(display.parse::<KeyCombo>().unwrap(), combo);
+        }
+        assert!("hyperr+x".parse::<KeyCombo>().is_err());
+        assert!("f36".parse::<KeyCombo>().is_err());
+    }
+
+    #[test]
+    fn test_sequences() {
+        let mut keymap = Keymap::new();
+        keymap.bind(Keymap::<&str>::GLOBAL, "g", "next").unwrap();
+        keymap.bind(Keymap::<&str>::GLOBAL, "g g", "top").unwrap();
+        keymap
+            .bind(Keymap::<&str>::GLOBAL, "ctrl+x ctrl+s", "save")
+            .unwrap();
+        keymap.bind(Keymap::<&str>::GLOBAL, "x", "delete").unwrap();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        assert_eq!(keymap.handle_at(&key("g"), at(0)), KeymapMatch::Pending);
+        assert_eq!(
+            keymap.handle_at(&key("g"), at(10)),
+            KeymapMatch::Action(&"top")
+        );
+
+        assert_eq!(
+            keymap.handle_at(&key("ctrl+x"), at(20)),
+            KeymapMatch::Pending
+        );
+        assert_eq!(
+            keymap.pending(),
+            &[key("ctrl+x")].map(|k| KeyCombo::from(&k))
+        );
+        assert_eq!(
+            keymap.handle_at(&key("ctrl+s"), at(30)),
+            KeymapMatch::Action(&"save")
+        );
+
+        // A key breaking the sequence is handled on its own
+        assert_eq!(
+            keymap.handle_at(&key("ctrl+x"), at(40)),
+            KeymapMatch::Pending
+        );
+        assert_eq!(
+            keymap.handle_at(&key("x"), at(50)),
+            KeymapMatch::Action(&"delete")
+        );
+        assert_eq!(keymap.handle_at(&key("y"), at(60)), KeymapMatch::Unbound);
+
+        // ... after the keys before, if they are bound on their own
+        assert_eq!(keymap.handle_at(&key("g"), at(70)), KeymapMatch::Pending);
+        let found = keymap.handle_at(&key("x"), at(80));
+        assert_eq!(found.actions(), vec![&"next", &"delete"]);
+        assert_eq!(
+            found,
+            KeymapMatch::Interrupted {
+                completed: &"next",
+                then: Box::new(KeymapMatch::Action(&"delete"))
+            }
+        );
+        assert_eq!(keymap.handle_at(&key("g"), at(90)), KeymapMatch::Pending);
+        let found = keymap.handle_at(&key("g"), at(95));
+        assert_eq!(found, KeymapMatch::Action(&"top"));
+        assert_eq!(keymap.handle_at(&key("g"), at(96)), KeymapMatch::Pending);
+        assert_eq!(
+            keymap.handle_at(&key("y"), at(97)),
+            KeymapMatch::Interrupted {
+                completed: &"next",
+                then: Box::new(KeymapMatch::Unbound)
+            }
+        );
+
+        // The shorter sequence is completed by the timeout
+        assert_eq!(keymap.handle_at(&key("g"), at(100)), KeymapMatch::Pending);
+        assert_eq!(keymap.flush_at(at(500)), None);
+        assert_eq!(keymap.flush_at(at(1100)), Some(&"next"));
+        assert!(keymap.pending().is_empty());
+
+        // A sequence continued too late starts over
+        assert_eq!(
+            keymap.handle_at(&key("ctrl+x"), at(2000)),
+            KeymapMatch::Pending
+        );
+        assert_eq!(
+            keymap.handle_at(&key("ctrl+s"), at(3100)),
+            KeymapMatch::Unbound
+        );
+    }
+
+    #[test]
+    fn test_modes_and_layers() {
+        let mut keymap = Keymap::new();
+        keymap.bind("normal", "i", "insert").unwrap();
+        keymap.bind("insert", "esc", "normal").unwrap();
+        keymap
+            .bind(Keymap::<&str>::GLOBAL, "ctrl+c", "quit")
+            .unwrap();
+        keymap
+            .bind(Keymap::<&str>::GLOBAL, "esc", "cancel")
+            .unwrap();
+        keymap.bind("popup", "ctrl+c", "close").unwrap();
+
+        assert_eq!(keymap.handle(&key("i")), KeymapMatch::Unbound);
+        keymap.set_mode("normal");
+        assert_eq!(keymap.handle(&key("i")), KeymapMatch::Action(&"insert"));
+        assert_eq!(keymap.handle(&key("esc")), KeymapMatch::Action(&"cancel"));
+        keymap.set_mode("insert");
+        assert_eq!(keymap.handle(&key("i")), KeymapMatch::Unbound);
+        assert_eq!(keymap.handle(&key("esc")), KeymapMatch::Action(&"normal"));
+        assert_eq!(keymap.handle(&key("ctrl+c")), KeymapMatch::Action(&"quit"));
+
+        keymap.push_layer("popup");
+        let actions: Vec<_> = keymap.active_bindings().iter().map(|b| b.action).collect();
+        assert_eq!(actions, vec!["close", "normal"]);
+        assert_eq!(keymap.handle(&key("ctrl+c")), KeymapMatch::Action(&"close"));
+        assert_eq!(keymap.pop_layer().as_deref(), Some("popup"));
+        assert_eq!(keymap.handle(&key("ctrl+c")), KeymapMatch::Action(&"quit"));
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = "
+            # Available everywhere
+            ctrl+c = quit
+
+            ctrl+equals = zoom in
+            hash = comment
+
+            [normal]
+            g g = go to top
+            ctrl+x ctrl+s = save
+        ";
+        let mut keymap: Keymap<String> = Keymap::from_config(config).unwrap();
+        assert_eq!(
+            keymap.handle(&key("ctrl+c")),
+            KeymapMatch::Action(&"quit".to_string())
+        );
+        assert_eq!(
+            keymap.handle(&key("ctrl+=")),
+            KeymapMatch::Action(&"zoom in".to_string())
+        );
+        assert_eq!(
+            keymap.handle(&key("#")),
+            KeymapMatch::Action(&"comment".to_string())
+        );
+        keymap.set_mode("normal");
+        assert_eq!(keymap.handle(&key("g")), KeymapMatch::Pending);
+        assert_eq!(
+            keymap.handle(&key("g")),
+            KeymapMatch::Action(&"go to top".to_string())
+        );
+
+        let error = Keymap::<String>::from_config("q = quit\nctrl+ = oops")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("line 2"), "{error}");
+        assert!(Keymap::<String>::from_config("[normal\nq = quit").is_err());
+        assert!(Keymap::<u8>::from_config("q = quit").is_err());
+    }
+}
@@ -39,9 +39,18 @@
 use std::{cmp::min, io::Read};
 
 mod event;
-pub use event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+pub use event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, KeyboardEnhancement,
+    MouseButton, MouseEvent, MouseEventKind, MouseMode,
+};
+mod gesture;
+pub use gesture::{Gesture, GestureDetector, GestureKind};
+mod keymap;
+pub use keymap::{Binding, KeyCombo, Keymap, KeymapMatch};
 mod parse;
 pub use parse::{InputParser, XtermParser};
+mod query;
+pub use query::Query;
 
 use crate::TalosResult;
 
@@ -57,6 +66,8 @@ pub struct Parser {
     pub buffer_linear_growth_step: usize,
     /// The maximum size of the `poll_input_buffer`
     pub max_poll_input_buffer: usize,
+    /// Recognises gestures in the parsed events, if set
+    pub gestures: Option<GestureDetector>,
 }
 
 /// Internal helper function
@@ -140,6 +151,7 @@ pub fn poll_input_bytes<'a, R: Read>(
 mod tests {
     use super::*;
     use std::io::Cursor;
+    use std::time::Duration;
 
     #[test]
     fn test_poll_input_parsing_branches() -> TalosResult<()> {
@@ -209,6 +221,391 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_bracketed_paste() -> TalosResult<()> {
+        let mut parser = XtermParser::new();
+        let mut events = Vec::new();
+
+        // The paste is split across reads, and contains line breaks and escape sequences
+        parser.parse(b"a\x1b[200~line 1\r\nline 2\rq\x1b[A", &mut events)?;
+        parser.flush(&mut events);
+        parser.parse(b"\x1b[201", &mut events)?;
+        parser.parse(b"~b", &mut events)?;
+
+        assert_eq!(
+            events,
+            vec![
+                Event::KeyEvent(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::default())),
+                Event::Paste("line 1\nline 2\nq\x1b[A".to_string()),
+                Event::KeyEvent(KeyEvent::new(KeyCode::Char('b'), KeyModifiers::default())),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_long_paste_in_parts() -> TalosResult<()> {
+        let mut parser = XtermParser::new().with_max_paste_len(8);
+        let mut events = Vec::new();
+
+        // Neither a character nor the end marker is split up
+        parser.parse("\x1b[200~abcdefgé€h\x1b[201~x".as_bytes(), &mut events)?;
+
+        assert_eq!(
+            events,
+            vec![
+                Event::Paste("abcdefg".to_string()),
+                Event::Paste("é€h".to_string()),
+                Event::KeyEvent(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::default())),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tiny_paste_limits() -> TalosResult<()> {
+        for max_paste_len in 1..=5 {
+            let mut parser = XtermParser::new().with_max_paste_len(max_paste_len);
+            let mut events = Vec::new();
+            parser.parse("\x1b[200~é".as_bytes(), &mut events)?;
+            // The end marker arrives byte by byte
+            for &byte in b"\x1b[201~x" {
+                parser.parse(&[byte], &mut events)?;
+            }
+
+            let text: String = events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::Paste(part) => Some(part.as_str()),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(text, "é", "limit {max_paste_len}");
+            assert_eq!(
+                events.last(),
+                Some(&Event::KeyEvent(KeyEvent::new(
+                    KeyCode::Char('x'),
+                    KeyModifiers::default()
+                ))),
+                "limit {max_paste_len}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_focus_events() -> TalosResult<()> {
+        let mut parser = XtermParser::new();
+        let mut events = Vec::new();
+        parser.parse(b"\x1b[O\x1b[Ix", &mut events)?;
+
+        assert_eq!(
+            events,
+            vec![
+                Event::FocusLost,
+                Event::FocusGained,
+                Event::KeyEvent(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::default())),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_kitty_keyboard() -> TalosResult<()> {
+        let mut parser = XtermParser::new();
+        let mut events = Vec::new();
+        // Ctrl+i, Super+a released, Esc, Shift+; with the shifted key, Ctrl+Up repeated, a with
+        // caps lock on, F13
+        parser.parse(
+            b"\x1b[105;5u\x1b[97;9:3u\x1b[27u\x1b[59:58;2u\x1b[1;5:2A\x1b[97;65u\x1b[57376u",
+            &mut events,
+        )?;
+
+        let key = |code, modifiers, kind, state| {
+            Event::KeyEvent(KeyEvent {
+                code,
+                modifiers,
+                kind,
+                state,
+            })
+        };
+        let ctrl = KeyModifiers {
+            ctrl: true,
+            none: false,
+            ..KeyModifiers::default()
+        };
+        let none = KeyModifiers::default();
+        let press = KeyEventKind::Press;
+        let unlocked = KeyEventState::default();
+        assert_eq!(
+            events,
+            vec![
+                key(KeyCode::Char('i'), ctrl, press, unlocked),
+                key(
+                    KeyCode::Char('a'),
+                    KeyModifiers {
+                        super_key: true,
+                        none: false,
+                        ..KeyModifiers::default()
+                    },
+                    KeyEventKind::Release,
+                    unlocked
+                ),
+                key(KeyCode::Esc, none, press, unlocked),
+                key(
+                    KeyCode::Char(':'),
+                    KeyModifiers {
+                        shift: true,
+                        none: false,
+                        ..KeyModifiers::default()
+                    },
+                    press,
+                    unlocked
+                ),
+                key(KeyCode::Up, ctrl, KeyEventKind::Repeat, unlocked),
+                key(
+                    KeyCode::Char('a'),
+                    none,
+                    press,
+                    KeyEventState {
+                        caps_lock: true,
+                        num_lock: false,
+                    }
+                ),
+                key(KeyCode::F(13), none, press, unlocked),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_kitty_keys_beyond_the_bmp() -> TalosResult<()> {
+        let mut parser = XtermParser::new();
+        let mut events = Vec::new();
+        // 😀, and Shift+𝐚 with 𝐀 as the shifted key
+        parser.parse(b"\x1b[128512u\x1b[119834:119808;2u", &mut events)?;
+
+        assert_eq!(
+            events,
+            vec![
+                Event::KeyEvent(KeyEvent::new(KeyCode::Char('😀'), KeyModifiers::default())),
+                Event::KeyEvent(KeyEvent::new(
+                    KeyCode::Char('𝐀'),
+                    KeyModifiers {
+                        shift: true,
+                        none: false,
+                        ..KeyModifiers::default()
+                    }
+                )),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_alt_keys() -> TalosResult<()> {
+        let mut parser = XtermParser::new();
+        let mut events = Vec::new();
+        // Alt+f, Ctrl+Alt+a, Alt+é, Alt+Enter
+        parser.parse(b"\x1bf\x1b\x01\x1b\xc3\xa9\x1b\r", &mut events)?;
+
+        let alt = KeyModifiers {
+            alt: true,
+            none: false,
+            ..KeyModifiers::default()
+        };
+        let ctrl_alt = KeyModifiers { ctrl: true, ..alt };
+        assert_eq!(
+            events,
+            vec![
+                Event::KeyEvent(KeyEvent::new(KeyCode::Char('f'), alt)),
+                Event::KeyEvent(KeyEvent::new(KeyCode::Char('a'), ctrl_alt)),
+                Event::KeyEvent(KeyEvent::new(KeyCode::Char('é'), alt)),
+                Event::KeyEvent(KeyEvent::new(KeyCode::Enter, alt)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_escape_timeout() -> TalosResult<()> {
+        let mut parser = XtermParser::new().with_escape_timeout(Duration::from_millis(20));
+        let mut events = Vec::new();
+
+        // The Escape key is held back until the timeout elapsed
+        parser.parse(b"\x1b", &mut events)?;
+        parser.flush(&mut events);
+        assert!(events.is_empty());
+        assert!(parser.is_pending());
+        std::thread::sleep(Duration::from_millis(25));
+        parser.flush(&mut events);
+        assert_eq!(
+            events,
+            vec![Event::KeyEvent(KeyEvent::new(
+                KeyCode::Esc,
+                KeyModifiers::default()
+            ))]
+        );
+
+        // `ESC O` followed by nothing is Alt+O
+        let mut parser = XtermParser::new().with_escape_timeout(Duration::ZERO);
+        events.clear();
+        parser.parse(b"\x1bO", &mut events)?;
+        parser.flush(&mut events);
+        assert_eq!(
+            events,
+            vec![Event::KeyEvent(KeyEvent::new(
+                KeyCode::Char('O'),
+                KeyModifiers {
+                    alt: true,
+                    none: false,
+                    ..KeyModifiers::default()
+                }
+            ))]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_and_keypad_keys() -> TalosResult<()> {
+        let mut parser = XtermParser::new();
+        let mut events = Vec::new();
+        // F13, F20, F12, Menu, Ctrl+F5, Shift+F1 (modified SS3), keypad 7, keypad enter,
+        // keypad begin, keypad '*' (kitty), Pause (kitty)
+        parser.parse(
+            b"\x1b[25~\x1b[34~\x1b[24~\x1b[29~\x1b[15;5~\x1bO2P\x1bOw\x1bOM\x1b[E\x1b[57411u\x1b[57362u",
+            &mut events,
+        )?;
+
+        let none = KeyModifiers::default();
+        let key = |code, modifiers| Event::KeyEvent(KeyEvent::new(code, modifiers));
+        assert_eq!(
+            events,
+            vec![
+                key(KeyCode::F(13), none),
+                key(KeyCode::F(20), none),
+                key(KeyCode::F(12), none),
+                key(KeyCode::Menu, none),
+                key(
+                    KeyCode::F(5),
+                    KeyModifiers {
+                        ctrl: true,
+                        none: false,
+                        ..none
+                    }
+                ),
+                key(
+                    KeyCode::F(1),
+                    KeyModifiers {
+                        shift: true,
+                        none: false,
+                        ..none
+                    }
+                ),
+                key(KeyCode::Keypad('7'), none),
+                key(KeyCode::KeypadEnter, none),
+                key(KeyCode::KeypadBegin, none),
+                key(KeyCode::Keypad('*'), none),
+                key(KeyCode::Pause, none),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mouse_encodings() -> TalosResult<()> {
+        let mut parser = XtermParser::new();
+        let mut events = Vec::new();
+        // SGR: back button pressed and released, wheel tilted right, Ctrl+movement
+        parser.parse(
+            b"\x1b[<128;5;3M\x1b[<128;5;3m\x1b[<67;1;1M\x1b[<51;2;2M",
+            &mut events,
+        )?;
+        // X10: middle button pressed and released at (10, 20)
+        parser.parse(b"\x1b[M!+5\x1b[M#+5", &mut events)?;
+        // urxvt: right button dragged to (100, 200)
+        parser.parse(b"\x1b[66;101;201M", &mut events)?;
+
+        let mouse = |kind, column, row, modifiers| {
+            Event::MouseEvent(MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers,
+            })
+        };
+        let none = KeyModifiers::default();
+        let ctrl = KeyModifiers {
+            ctrl: true,
+            none: false,
+            ..none
+        };
+        assert_eq!(
+            events,
+            vec![
+                mouse(MouseEventKind::Down(MouseButton::Back), 4, 2, none),
+                mouse(MouseEventKind::Up(MouseButton::Back), 4, 2, none),
+                mouse(MouseEventKind::ScrollRight, 0, 0, none),
+                mouse(MouseEventKind::Moved, 1, 1, ctrl),
+                mouse(MouseEventKind::Down(MouseButton::Middle), 10, 20, none),
+                mouse(MouseEventKind::Up(MouseButton::Middle), 10, 20, none),
+                mouse(MouseEventKind::Drag(MouseButton::Right), 100, 200, none),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_responses() -> TalosResult<()> {
+        let mut parser = XtermParser::new();
+        let mut events = Vec::new();
+        // Colour reports, terminated by ST and BEL
+        parser.parse(
+            b"\x1b]11;rgb:1e1e/2020/3030\x1b\\\x1b]10;rgb:f/8/0\x07",
+            &mut events,
+        )?;
+        // Pixel sizes of the window and of a cell
+        parser.parse(b"\x1b[4;600;800t\x1b[6;20;10t", &mut events)?;
+        // A cursor position report - the first row only when asked for, Shift+F3 otherwise
+        parser.parse(b"\x1b[5;10R\x1b[1;2R", &mut events)?;
+        parser.expect_cursor_position();
+        parser.parse(b"\x1b[1;2R", &mut events)?;
+        // Not a command - Alt+]
+        parser.parse(b"\x1b]x", &mut events)?;
+
+        let shift = KeyModifiers {
+            shift: true,
+            none: false,
+            ..KeyModifiers::default()
+        };
+        let alt = KeyModifiers {
+            alt: true,
+            none: false,
+            ..KeyModifiers::default()
+        };
+        assert_eq!(
+            events,
+            vec![
+                Event::BackgroundColour(0x1e, 0x20, 0x30),
+                Event::ForegroundColour(0xff, 0x88, 0x00),
+                Event::WindowPixelSize {
+                    width: 800,
+                    height: 600
+                },
+                Event::CellPixelSize {
+                    width: 10,
+                    height: 20
+                },
+                Event::CursorPosition { column: 9, row: 4 },
+                Event::KeyEvent(KeyEvent::new(KeyCode::F(3), shift)),
+                Event::CursorPosition { column: 1, row: 0 },
+                Event::KeyEvent(KeyEvent::new(KeyCode::Char(']'), alt)),
+                Event::KeyEvent(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::default())),
+            ]
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_empty_input() -> TalosResult<()> {
         let mut reader = Cursor::new(vec![]);
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::error::TalosResult;
 
 use super::Event;
@@ -53,4 +55,25 @@ pub trait InputParser {
     /// # Arguments
     /// * `output` - The output buffer to write events to
     fn flush(&mut self, output: &mut Vec<Event>);
+    /// Returns whether the parser holds an incomplete sequence, that will be emitted by `flush`
+    ///
+    /// Used to avoid blocking on new input while an event is still pending.
+    fn is_pending(&self) -> bool {
+        false
+    }
+    /// Returns how much longer `flush` holds back a pending sequence
+    ///
+    /// `Talos::wait_input` waits at most this long while `is_pending` is true.
+    fn pending_timeout(&self) -> Duration {
+        Duration::ZERO
+    }
+    /// Called when the cursor position was queried, before the answer arrives
+    ///
+    /// The answer `ESC [ 1 ; 2 R` looks just like Shift+F3 - parsers may use this to tell them
+    /// apart.
+    fn expect_cursor_position(&mut self) {}
+    /// Called when waiting for the answer to a cursor position query was given up on
+    ///
+    /// Undoes one `expect_cursor_position`.
+    fn cancel_cursor_position(&mut self) {}
 }
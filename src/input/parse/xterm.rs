@@ -1,8 +1,13 @@
+use std::time::{Duration, Instant};
+
 use crate::{
     error::TalosResult,
     input::{
         Event,
-        event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
+        event::{
+            KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, MouseButton, MouseEvent,
+            MouseEventKind,
+        },
     },
 };
 
@@ -15,16 +20,53 @@ enum ParserState {
     Esc,
     Csi,
     Ss3,
+    Paste,
+    /// The three bytes of an X10 mouse report, after `ESC [ M`
+    X10Mouse,
+    /// An operating system command, e.g. a colour report - after `ESC ]`
+    Osc,
 }
 
+/// Ends a bracketed paste
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// Operating system commands longer than this are given up on
+const MAX_OSC_LEN: usize = 256;
+
 /// The Xterm input parser
+///
+/// Keys pressed together with Alt arrive as `ESC` followed by the key. A lone `ESC` is only
+/// reported as the Escape key once the escape timeout elapsed without another byte arriving, see
+/// `with_escape_timeout`.
+#[allow(clippy::struct_excessive_bools)]
 pub struct XtermParser {
     state: ParserState,
-    params: Vec<u16>,
-    current_param: u16,
+    /// Wide enough for any Unicode scalar value, as the kitty keyboard protocol sends them
+    params: Vec<u32>,
+    current_param: u32,
     has_param_digit: bool,
     pending_buffer: Vec<u8>,
     is_sgr_mouse: bool,
+    /// Set for private sequences (`ESC [ ?`) - answers of the terminal, not input
+    is_private: bool,
+    /// `(param index, value)` of all sub-parameters, separated by `:` - e.g. `ESC [ 1 ; 5 : 3 A`
+    sub_params: Vec<(usize, u32)>,
+    in_sub_param: bool,
+    paste_buffer: Vec<u8>,
+    /// A paste is reported in parts once it grows this long
+    max_paste_len: usize,
+    /// A part of the current paste was reported already
+    paste_continued: bool,
+    osc_buffer: Vec<u8>,
+    /// Cursor position reports asked for, but not received yet
+    expected_cursor_reports: usize,
+    /// The next key is pressed together with Alt
+    alt: bool,
+    /// Legacy mouse encodings do not report which button was released
+    last_mouse_button: MouseButton,
+    escape_timeout: Duration,
+    /// When the pending `ESC` was received
+    escape_received: Instant,
 }
 
 impl InputParser for XtermParser {
@@ -36,6 +78,18 @@ impl InputParser for XtermParser {
             has_param_digit: false,
             pending_buffer: Vec::with_capacity(32),
             is_sgr_mouse: false,
+            is_private: false,
+            sub_params: Vec::new(),
+            in_sub_param: false,
+            paste_buffer: Vec::new(),
+            max_paste_len: Self::DEFAULT_MAX_PASTE_LEN,
+            paste_continued: false,
+            osc_buffer: Vec::new(),
+            expected_cursor_reports: 0,
+            alt: false,
+            last_mouse_button: MouseButton::Left,
+            escape_timeout: Self::DEFAULT_ESCAPE_TIMEOUT,
+            escape_received: Instant::now(),
         }
     }
 
@@ -46,23 +100,120 @@ impl InputParser for XtermParser {
                 ParserState::Esc => self.handle_esc(byte, output),
                 ParserState::Csi => self.handle_csi(byte, output),
                 ParserState::Ss3 => self.handle_ss3(byte, output),
+                ParserState::Paste => self.handle_paste(byte, output),
+                ParserState::X10Mouse => self.handle_x10_mouse(byte, output),
+                ParserState::Osc => self.handle_osc(byte, output),
             }
         }
         Ok(())
     }
 
     fn flush(&mut self, output: &mut Vec<Event>) {
-        if self.state == ParserState::Esc {
-            output.push(Event::KeyEvent(KeyEvent::new(
-                KeyCode::Esc,
-                KeyModifiers::default(),
-            )));
-            self.reset_state();
+        if !self.is_pending() || self.escape_received.elapsed() < self.escape_timeout {
+            return;
+        }
+        // Nothing followed - `ESC [`, `ESC O` and `ESC ]` were Alt+[, Alt+O and Alt+] after all
+        let code = match self.state {
+            ParserState::Csi => KeyCode::Char('['),
+            ParserState::Ss3 => KeyCode::Char('O'),
+            ParserState::Osc => KeyCode::Char(']'),
+            _ => KeyCode::Esc,
+        };
+        let modifiers = if code == KeyCode::Esc {
+            KeyModifiers::default()
+        } else {
+            ALT
+        };
+        output.push(Event::KeyEvent(KeyEvent::new(code, modifiers)));
+        self.reset_state();
+    }
+
+    fn is_pending(&self) -> bool {
+        match self.state {
+            ParserState::Esc => true,
+            ParserState::Ss3 => self.params.is_empty() && !self.has_param_digit,
+            ParserState::Csi => {
+                self.params.is_empty()
+                    && !self.has_param_digit
+                    && !self.is_sgr_mouse
+                    && !self.is_private
+            }
+            ParserState::Osc => self.osc_buffer.is_empty(),
+            ParserState::Normal | ParserState::Paste | ParserState::X10Mouse => false,
         }
     }
+
+    fn pending_timeout(&self) -> Duration {
+        self.escape_timeout
+            .saturating_sub(self.escape_received.elapsed())
+    }
+
+    fn expect_cursor_position(&mut self) {
+        self.expected_cursor_reports += 1;
+    }
+
+    fn cancel_cursor_position(&mut self) {
+        self.expected_cursor_reports = self.expected_cursor_reports.saturating_sub(1);
+    }
 }
 
+/// The modifiers of a key pressed together with Alt
+const ALT: KeyModifiers = KeyModifiers {
+    none: false,
+    shift: false,
+    ctrl: false,
+    alt: true,
+    super_key: false,
+    hyper: false,
+    meta: false,
+};
+
 impl XtermParser {
+    /// How long to wait for the rest of an escape sequence by default
+    pub const DEFAULT_ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+
+    /// How many bytes of a paste are collected by default before reporting a part of it
+    pub const DEFAULT_MAX_PASTE_LEN: usize = 1024 * 1024;
+
+    /// Sets how long to wait for the rest of an escape sequence
+    ///
+    /// An `ESC` followed by nothing within this time is reported as the Escape key. Increase it if
+    /// Alt+key or special keys are split up over slow connections, e.g. SSH. Decrease it to report
+    /// the Escape key quicker.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use talos::{ParserBuilder, input::{InputParser, XtermParser}};
+    ///
+    /// let xterm = XtermParser::new().with_escape_timeout(Duration::from_millis(100));
+    /// let parser = ParserBuilder::default().with_input_parser(Box::new(xterm)).build();
+    /// ```
+    #[must_use]
+    pub fn with_escape_timeout(mut self, escape_timeout: Duration) -> Self {
+        self.escape_timeout = escape_timeout;
+        self
+    }
+
+    /// Sets how many bytes of a paste are collected before reporting a part of it
+    ///
+    /// Longer pastes arrive as several `Event::Paste`, so that pasting huge amounts of text does
+    /// not use up memory. The default is around 1MB.
+    ///
+    /// # Example
+    /// ```rust
+    /// use talos::{ParserBuilder, input::{InputParser, XtermParser}};
+    ///
+    /// let xterm = XtermParser::new().with_max_paste_len(64 * 1024);
+    /// let parser = ParserBuilder::default().with_input_parser(Box::new(xterm)).build();
+    /// ```
+    #[must_use]
+    pub fn with_max_paste_len(mut self, max_paste_len: usize) -> Self {
+        // Shorter limits would report the start of the end marker as text
+        self.max_paste_len = max_paste_len.max(PASTE_END.len());
+        self
+    }
+
     /// Resets the parser state
     fn reset_state(&mut self) {
         self.state = ParserState::Normal;
@@ -71,28 +222,67 @@ impl XtermParser {
         self.has_param_digit = false;
         self.pending_buffer.clear();
         self.is_sgr_mouse = false;
+        self.is_private = false;
+        self.sub_params.clear();
+        self.in_sub_param = false;
+        self.alt = false;
+    }
+
+    /// Adds Alt to the modifiers of `event`, if it was preceded by `ESC`
+    fn with_pending_alt(&mut self, mut event: Event) -> Event {
+        if let Event::KeyEvent(key) = &mut event
+            && std::mem::take(&mut self.alt)
+        {
+            key.modifiers.alt = true;
+            key.modifiers.none = false;
+        }
+        event
+    }
+
+    /// Returns the `n`-th sub-parameter of the parameter at `index`
+    fn sub_param(&self, index: usize, n: usize) -> Option<u32> {
+        self.sub_params
+            .iter()
+            .filter(|(i, _)| *i == index)
+            .nth(n)
+            .map(|(_, value)| *value)
+    }
+
+    /// Ends the current parameter or sub-parameter
+    fn push_param(&mut self) {
+        if self.in_sub_param {
+            self.sub_params
+                .push((self.params.len() - 1, self.current_param));
+        } else {
+            self.params.push(self.current_param);
+        }
+        self.current_param = 0;
+        self.has_param_digit = false;
     }
 
     /// Handles a byte in the normal state
     fn handle_normal(&mut self, byte: u8, output: &mut Vec<Event>) {
         if byte == 0x1B {
             self.state = ParserState::Esc;
+            self.escape_received = Instant::now();
             return;
         }
 
         // Handle Control Codes and UTF-8
         if byte < 32 || byte == 127 {
             if let Some(event) = parse_control_byte(byte) {
+                let event = self.with_pending_alt(event);
                 output.push(event);
             }
         } else {
             // Basic UTF-8 accumulation
             self.pending_buffer.push(byte);
             if let Some((ch, len)) = try_parse_utf8(&self.pending_buffer) {
-                output.push(Event::KeyEvent(KeyEvent::new(
+                let event = self.with_pending_alt(Event::KeyEvent(KeyEvent::new(
                     KeyCode::Char(ch),
                     KeyModifiers::default(),
                 )));
+                output.push(event);
                 self.pending_buffer.drain(..len);
             }
         }
@@ -103,13 +293,22 @@ impl XtermParser {
         match byte {
             b'[' => self.state = ParserState::Csi,
             b'O' => self.state = ParserState::Ss3,
-            _ => {
-                // Not a known sequence starter, treat previous ESC as key and re-process byte
+            b']' => {
+                self.state = ParserState::Osc;
+                self.osc_buffer.clear();
+            }
+            0x1B => {
+                // Escape pressed twice - the second one may start a sequence
                 output.push(Event::KeyEvent(KeyEvent::new(
                     KeyCode::Esc,
                     KeyModifiers::default(),
                 )));
+                self.escape_received = Instant::now();
+            }
+            _ => {
+                // Not a known sequence starter - the key was pressed together with Alt
                 self.state = ParserState::Normal;
+                self.alt = true;
                 self.handle_normal(byte, output);
             }
         }
@@ -119,26 +318,64 @@ impl XtermParser {
     fn handle_csi(&mut self, byte: u8, output: &mut Vec<Event>) {
         match byte {
             b'<' => self.is_sgr_mouse = true,
+            b'?' => self.is_private = true,
             b'0'..=b'9' => {
                 self.current_param = self
                     .current_param
                     .saturating_mul(10)
-                    .saturating_add(u16::from(byte - b'0'));
+                    .saturating_add(u32::from(byte - b'0'));
                 self.has_param_digit = true;
             }
             b';' => {
-                self.params.push(self.current_param);
-                self.current_param = 0;
-                self.has_param_digit = false;
+                self.push_param();
+                self.in_sub_param = false;
+            }
+            b':' => {
+                self.push_param();
+                self.in_sub_param = true;
             }
             0x40..=0x7E => {
                 // Final Byte
                 if self.has_param_digit {
-                    self.params.push(self.current_param);
+                    self.push_param();
+                }
+
+                // Start of a bracketed paste - everything up to the end marker is pasted text
+                if byte == b'~' && self.params == [200] {
+                    self.reset_state();
+                    self.state = ParserState::Paste;
+                    return;
+                }
+
+                // X10 mouse report - the button and position follow as raw bytes
+                if byte == b'M' && self.params.is_empty() && !self.is_sgr_mouse && !self.is_private
+                {
+                    self.reset_state();
+                    self.state = ParserState::X10Mouse;
+                    return;
                 }
 
-                let event = if self.is_sgr_mouse {
+                let event = if self.is_private {
+                    None
+                } else if self.is_sgr_mouse {
                     self.parse_sgr_mouse(byte)
+                } else if byte == b'M' && self.params.len() == 3 {
+                    // urxvt format: ESC [ button ; column ; row M - the button is offset by 32
+                    self.parse_mouse(
+                        self.params[0].saturating_sub(32),
+                        self.params[1],
+                        self.params[2],
+                        false,
+                    )
+                } else if byte == b'u' {
+                    self.parse_kitty_key()
+                } else if byte == b'R' && self.is_cursor_position_report() {
+                    // ESC [ row ; column R
+                    self.expected_cursor_reports = self.expected_cursor_reports.saturating_sub(1);
+                    Some(Event::CursorPosition {
+                        column: clamp_param(self.params[1].saturating_sub(1)),
+                        row: clamp_param(self.params[0].saturating_sub(1)),
+                    })
                 } else {
                     self.finalize_csi(byte)
                 };
@@ -152,18 +389,140 @@ impl XtermParser {
         }
     }
 
+    /// Handles a byte in the Paste state
+    fn handle_paste(&mut self, byte: u8, output: &mut Vec<Event>) {
+        self.paste_buffer.push(byte);
+        if self.paste_buffer.ends_with(PASTE_END) {
+            self.paste_buffer
+                .truncate(self.paste_buffer.len() - PASTE_END.len());
+            // The last part may be empty if the end marker followed right after the previous one
+            if !self.paste_buffer.is_empty() || !self.paste_continued {
+                output.push(paste_event(&self.paste_buffer));
+            }
+            self.paste_buffer.clear();
+            self.paste_continued = false;
+            self.reset_state();
+        } else if self.paste_buffer.len() >= self.max_paste_len {
+            // Report what was collected so far, the paste goes on
+            let split = paste_split(&self.paste_buffer);
+            if split > 0 {
+                let rest = self.paste_buffer.split_off(split);
+                let part = std::mem::replace(&mut self.paste_buffer, rest);
+                output.push(paste_event(&part));
+                self.paste_continued = true;
+            }
+        }
+    }
+
+    /// Handles a byte in the X10 mouse state
+    ///
+    /// X10 format: ESC [ M button column row - each a single byte, offset by 32
+    fn handle_x10_mouse(&mut self, byte: u8, output: &mut Vec<Event>) {
+        self.params.push(u32::from(byte.saturating_sub(32)));
+        if self.params.len() < 3 {
+            return;
+        }
+        if let Some(event) = self.parse_mouse(self.params[0], self.params[1], self.params[2], false)
+        {
+            output.push(event);
+        }
+        self.reset_state();
+    }
+
+    /// Handles a byte in the OSC state
+    ///
+    /// Format: `ESC ] command ; data` terminated by `BEL` or `ESC \`
+    fn handle_osc(&mut self, byte: u8, output: &mut Vec<Event>) {
+        if self.osc_buffer.is_empty() && !byte.is_ascii_digit() {
+            // Not a command - the key was pressed together with Alt
+            self.state = ParserState::Normal;
+            output.push(Event::KeyEvent(KeyEvent::new(KeyCode::Char(']'), ALT)));
+            self.handle_normal(byte, output);
+            return;
+        }
+
+        self.osc_buffer.push(byte);
+        let command_len = if byte == 0x07 {
+            self.osc_buffer.len() - 1
+        } else if self.osc_buffer.ends_with(b"\x1b\\") {
+            self.osc_buffer.len() - 2
+        } else {
+            if self.osc_buffer.len() > MAX_OSC_LEN {
+                let mut bytes = vec![0x1B, b']'];
+                bytes.append(&mut self.osc_buffer);
+                output.push(Event::Unknown(bytes));
+                self.reset_state();
+            }
+            return;
+        };
+
+        let command = &self.osc_buffer[..command_len];
+        let event = parse_osc(command).unwrap_or_else(|| {
+            let mut bytes = vec![0x1B, b']'];
+            bytes.extend_from_slice(command);
+            Event::Unknown(bytes)
+        });
+        output.push(event);
+        self.osc_buffer.clear();
+        self.reset_state();
+    }
+
     /// Handles a byte in the SS3 state
+    ///
+    /// Sent for F1 - F4, and for the cursor and keypad keys in application mode. Some terminals
+    /// put the modifiers in between, e.g. `ESC O 2 P` for Shift+F1.
     fn handle_ss3(&mut self, byte: u8, output: &mut Vec<Event>) {
+        match byte {
+            b'0'..=b'9' => {
+                self.current_param = self
+                    .current_param
+                    .saturating_mul(10)
+                    .saturating_add(u32::from(byte - b'0'));
+                self.has_param_digit = true;
+                return;
+            }
+            b';' => {
+                self.push_param();
+                return;
+            }
+            _ => {}
+        }
+        if self.has_param_digit {
+            self.push_param();
+        }
+        let (modifiers, state) = parse_modifier_param(self.params.last().copied().unwrap_or(1));
+
         let code = match byte {
             b'P' => Some(KeyCode::F(1)),
             b'Q' => Some(KeyCode::F(2)),
             b'R' => Some(KeyCode::F(3)),
             b'S' => Some(KeyCode::F(4)),
+            b'A' => Some(KeyCode::Up),
+            b'B' => Some(KeyCode::Down),
+            b'C' => Some(KeyCode::Right),
+            b'D' => Some(KeyCode::Left),
+            b'H' => Some(KeyCode::Home),
+            b'F' => Some(KeyCode::End),
+            b'E' => Some(KeyCode::KeypadBegin),
+            b'M' => Some(KeyCode::KeypadEnter),
+            b'p'..=b'y' => Some(KeyCode::Keypad(char::from(byte - b'p' + b'0'))),
+            b'j' => Some(KeyCode::Keypad('*')),
+            b'k' => Some(KeyCode::Keypad('+')),
+            b'l' => Some(KeyCode::Keypad(',')),
+            b'm' => Some(KeyCode::Keypad('-')),
+            b'n' => Some(KeyCode::Keypad('.')),
+            b'o' => Some(KeyCode::Keypad('/')),
+            b'X' => Some(KeyCode::Keypad('=')),
             _ => None,
         };
 
-        if let Some(k) = code {
-            output.push(Event::KeyEvent(KeyEvent::new(k, KeyModifiers::default())));
+        if let Some(code) = code {
+            output.push(Event::KeyEvent(KeyEvent {
+                code,
+                modifiers,
+                kind: KeyEventKind::Press,
+                state,
+            }));
         } else {
             output.push(Event::Unknown(vec![0x1B, b'O', byte]));
         }
@@ -177,7 +536,7 @@ impl XtermParser {
         } else {
             1
         };
-        let modifiers = parse_modifier_param(modifier_param);
+        let (modifiers, state) = parse_modifier_param(modifier_param);
 
         let key_code = match final_byte {
             b'A' => Some(KeyCode::Up),
@@ -186,51 +545,158 @@ impl XtermParser {
             b'D' => Some(KeyCode::Left),
             b'H' => Some(KeyCode::Home),
             b'F' => Some(KeyCode::End),
+            b'P' => Some(KeyCode::F(1)),
+            b'Q' => Some(KeyCode::F(2)),
+            b'R' => Some(KeyCode::F(3)),
+            b'S' => Some(KeyCode::F(4)),
+            b'I' => return Some(Event::FocusGained),
+            b'O' => return Some(Event::FocusLost),
             b'Z' => {
                 return Some(Event::KeyEvent(KeyEvent::new(
                     KeyCode::Tab,
                     KeyModifiers {
                         shift: true,
                         none: false,
-                        ctrl: false,
-                        alt: false,
+                        ..KeyModifiers::default()
                     },
                 )));
             }
-            b'~' => match self.params.first().copied().unwrap_or(0) {
-                1 => Some(KeyCode::Home),
-                2 => Some(KeyCode::Insert),
-                3 => Some(KeyCode::Delete),
-                4 => Some(KeyCode::End),
-                5 => Some(KeyCode::PageUp),
-                6 => Some(KeyCode::PageDown),
-                11..=15 | 17..=21 | 23..=24 => {
-                    let f_map = [0, 1, 2, 3, 4, 5, 0, 6, 7, 8, 9, 10, 0, 11, 12];
-                    let idx = (self.params[0] - 11) as usize;
-                    if idx < f_map.len() && f_map[idx] != 0 {
-                        Some(KeyCode::F(f_map[idx]))
-                    } else {
-                        None
-                    }
-                }
-                _ => None,
-            },
+            // The keypad 5, or the center key of the keypad with num lock off
+            b'E' | b'G' => Some(KeyCode::KeypadBegin),
+            b'~' => tilde_key_code(self.params.first().copied().unwrap_or(0)),
+            b't' => return self.parse_window_report(),
             _ => None,
         };
 
-        key_code.map(|code| Event::KeyEvent(KeyEvent::new(code, modifiers)))
+        key_code.map(|code| {
+            Event::KeyEvent(KeyEvent {
+                code,
+                modifiers,
+                kind: self.key_event_kind(),
+                state,
+            })
+        })
+    }
+
+    /// Returns whether the `ESC [ ... R` sequence is a cursor position report
+    ///
+    /// Shift+F3 and the like are sent as `ESC [ 1 ; modifiers R` - a report for the first row
+    /// looks the same, so it is only taken for one if it was asked for.
+    fn is_cursor_position_report(&self) -> bool {
+        self.params.len() == 2 && (self.params[0] != 1 || self.expected_cursor_reports > 0)
+    }
+
+    /// Finalizes a window report - `ESC [ 4 ; height ; width t` for the size of the text area and
+    /// `ESC [ 6 ; height ; width t` for the size of a cell, both in pixels
+    fn parse_window_report(&self) -> Option<Event> {
+        match *self.params.as_slice() {
+            [4, height, width] => Some(Event::WindowPixelSize {
+                width: clamp_param(width),
+                height: clamp_param(height),
+            }),
+            [6, height, width] => Some(Event::CellPixelSize {
+                width: clamp_param(width),
+                height: clamp_param(height),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Finalizes a kitty keyboard protocol sequence
+    ///
+    /// Format: `ESC [ code : shifted : base ; modifiers : kind ; text u`
+    fn parse_kitty_key(&self) -> Option<Event> {
+        let code = *self.params.first()?;
+        let (modifiers, state) = parse_modifier_param(self.params.get(1).copied().unwrap_or(1));
+
+        let code = match code {
+            27 => KeyCode::Esc,
+            13 => KeyCode::Enter,
+            9 => KeyCode::Tab,
+            127 | 8 => KeyCode::Backspace,
+            // F13 - F35
+            57376..=57398 => KeyCode::F(u8::try_from(code - 57376 + 13).ok()?),
+            57361 => KeyCode::PrintScreen,
+            57362 => KeyCode::Pause,
+            57363 => KeyCode::Menu,
+            // The keypad
+            57399..=57408 => KeyCode::Keypad(char::from_digit(code - 57399, 10)?),
+            57409 => KeyCode::Keypad('.'),
+            57410 => KeyCode::Keypad('/'),
+            57411 => KeyCode::Keypad('*'),
+            57412 => KeyCode::Keypad('-'),
+            57413 => KeyCode::Keypad('+'),
+            57414 => KeyCode::KeypadEnter,
+            57415 => KeyCode::Keypad('='),
+            57416 => KeyCode::Keypad(','),
+            57417 => KeyCode::Left,
+            57418 => KeyCode::Right,
+            57419 => KeyCode::Up,
+            57420 => KeyCode::Down,
+            57421 => KeyCode::PageUp,
+            57422 => KeyCode::PageDown,
+            57423 => KeyCode::Home,
+            57424 => KeyCode::End,
+            57425 => KeyCode::Insert,
+            57426 => KeyCode::Delete,
+            57427 => KeyCode::KeypadBegin,
+            // Lock, media and modifier keys on their own
+            57344..=63743 => return None,
+            _ => {
+                // Prefer the shifted key, if the terminal reports it
+                let shifted = self
+                    .sub_param(0, 0)
+                    .filter(|&shifted| modifiers.shift && shifted != 0)
+                    .and_then(char::from_u32);
+                let ch = char::from_u32(code)?;
+                match shifted {
+                    Some(shifted) => KeyCode::Char(shifted),
+                    None if modifiers.shift => KeyCode::Char(ch.to_ascii_uppercase()),
+                    None => KeyCode::Char(ch),
+                }
+            }
+        };
+
+        Some(Event::KeyEvent(KeyEvent {
+            code,
+            modifiers,
+            kind: self.key_event_kind(),
+            state,
+        }))
+    }
+
+    /// The kind of key event, reported by the kitty keyboard protocol as a sub-parameter of the
+    /// modifiers
+    fn key_event_kind(&self) -> KeyEventKind {
+        match self.sub_param(1, 0) {
+            Some(2) => KeyEventKind::Repeat,
+            Some(3) => KeyEventKind::Release,
+            _ => KeyEventKind::Press,
+        }
     }
 
     /// Finalizes a SGR mouse sequence
-    fn parse_sgr_mouse(&self, final_byte: u8) -> Option<Event> {
+    fn parse_sgr_mouse(&mut self, final_byte: u8) -> Option<Event> {
         // SGR format: ESC [ < button ; column ; row (m or M)
         if self.params.len() < 3 {
             return None;
         }
+        // final_byte 'M' is press/drag, 'm' is release
+        self.parse_mouse(
+            self.params[0],
+            self.params[1],
+            self.params[2],
+            final_byte == b'm',
+        )
+    }
 
-        let b_code = self.params[0];
-        let col = self.params[1].saturating_sub(1);
-        let row = self.params[2].saturating_sub(1);
+    /// Decodes a mouse report, shared by all encodings
+    ///
+    /// `column` and `row` are 1-based. Only the SGR encoding reports releases with `release` -
+    /// the legacy encodings use the button code 3 instead, without saying which button it was.
+    fn parse_mouse(&mut self, b_code: u32, column: u32, row: u32, release: bool) -> Option<Event> {
+        let col = clamp_param(column.saturating_sub(1));
+        let row = clamp_param(row.saturating_sub(1));
 
         // Bit 2: Shift, Bit 3: Alt, Bit 4: Ctrl
         let modifiers = KeyModifiers {
@@ -238,55 +704,169 @@ impl XtermParser {
             alt: (b_code & 8) != 0,
             ctrl: (b_code & 16) != 0,
             none: (b_code & 28) == 0,
+            ..KeyModifiers::default()
         };
 
-        // b_code bits 0-1 and 6-7 determine the button/event type
-        // final_byte 'M' is press/drag, 'm' is release
-        let kind = match (final_byte, b_code & 0x63) {
-            (b'M', 0) => MouseEventKind::Down(MouseButton::Left),
-            (b'M', 1) => MouseEventKind::Down(MouseButton::Middle),
-            (b'M', 2) => MouseEventKind::Down(MouseButton::Right),
-            (b'm', 0) => MouseEventKind::Up(MouseButton::Left),
-            (b'm', 1) => MouseEventKind::Up(MouseButton::Middle),
-            (b'm', 2) => MouseEventKind::Up(MouseButton::Right),
-            (b'M', 32) => MouseEventKind::Drag(MouseButton::Left),
-            (b'M', 33) => MouseEventKind::Drag(MouseButton::Middle),
-            (b'M', 34) => MouseEventKind::Drag(MouseButton::Right),
-            (b'M', 35) => MouseEventKind::Moved,
-            (b'M', 64) => MouseEventKind::ScrollUp,
-            (b'M', 65) => MouseEventKind::ScrollDown,
-            _ => return None,
+        // b_code bits 0-1 and 6-7 determine the button, bit 5 is set for movement
+        let motion = (b_code & 32) != 0;
+        let kind = match b_code & 0xC3 {
+            64 => MouseEventKind::ScrollUp,
+            65 => MouseEventKind::ScrollDown,
+            66 => MouseEventKind::ScrollLeft,
+            67 => MouseEventKind::ScrollRight,
+            // No button - movement, or a release in the legacy encodings
+            3 if motion => MouseEventKind::Moved,
+            3 => MouseEventKind::Up(self.last_mouse_button),
+            code => {
+                let button = match code {
+                    0 => MouseButton::Left,
+                    1 => MouseButton::Middle,
+                    2 => MouseButton::Right,
+                    128 => MouseButton::Back,
+                    129 => MouseButton::Forward,
+                    130 => MouseButton::Other(10),
+                    131 => MouseButton::Other(11),
+                    _ => return None,
+                };
+                if motion {
+                    MouseEventKind::Drag(button)
+                } else if release {
+                    MouseEventKind::Up(button)
+                } else {
+                    self.last_mouse_button = button;
+                    MouseEventKind::Down(button)
+                }
+            }
         };
 
-        Some(Event::MouseEvent(MouseEvent {
-            kind,
-            column: col,
-            row,
-            modifiers,
-        }))
+        Some(mouse_event(kind, col, row, modifiers))
     }
 }
 
-fn parse_modifier_param(param: u16) -> KeyModifiers {
-    let (shift, alt, ctrl) = match param {
-        2 => (true, false, false),
-        3 => (false, true, false),
-        4 => (true, true, false),
-        5 => (false, false, true),
-        6 => (true, false, true),
-        7 => (false, true, true),
-        8 => (true, true, true),
-        _ => (false, false, false),
+fn mouse_event(kind: MouseEventKind, column: u16, row: u16, modifiers: KeyModifiers) -> Event {
+    Event::MouseEvent(MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers,
+    })
+}
+
+/// Reports a (part of a) paste, with line breaks normalised to `\n`
+fn paste_event(bytes: &[u8]) -> Event {
+    let text = String::from_utf8_lossy(bytes)
+        .replace("\r\n", "\n")
+        .replace('\r', "\n");
+    Event::Paste(text)
+}
+
+/// Returns where to split a paste without cutting a character or what may be the start of the
+/// end marker - `0` if nothing can be reported yet
+fn paste_split(bytes: &[u8]) -> usize {
+    let tail = bytes.len().saturating_sub(PASTE_END.len() - 1);
+    let mut split = bytes[tail..]
+        .iter()
+        .position(|&b| b == 0x1B)
+        .map_or(bytes.len(), |i| tail + i);
+    if let Err(e) = std::str::from_utf8(&bytes[..split])
+        && e.error_len().is_none()
+    {
+        split = e.valid_up_to();
+    }
+    split
+}
+
+/// Clamps a parameter to a position or size - larger values are sent by no terminal
+fn clamp_param(param: u32) -> u16 {
+    u16::try_from(param).unwrap_or(u16::MAX)
+}
+
+/// Decodes the key of `ESC [ code ~` sequences
+///
+/// F13 - F20 are sent by the Linux console and VT220-like terminals - xterm-like terminals send
+/// them as Shift+F1 - Shift+F12 instead. Code 29 is the Menu key for xterm and kitty, but F16 for
+/// the Linux console.
+fn tilde_key_code(code: u32) -> Option<KeyCode> {
+    let key_code = match code {
+        1 | 7 => KeyCode::Home,
+        2 => KeyCode::Insert,
+        3 => KeyCode::Delete,
+        4 | 8 => KeyCode::End,
+        5 => KeyCode::PageUp,
+        6 => KeyCode::PageDown,
+        11..=15 => KeyCode::F(u8::try_from(code - 10).ok()?),
+        17..=21 => KeyCode::F(u8::try_from(code - 11).ok()?),
+        23..=26 => KeyCode::F(u8::try_from(code - 12).ok()?),
+        28 => KeyCode::F(15),
+        29 => KeyCode::Menu,
+        31..=34 => KeyCode::F(u8::try_from(code - 14).ok()?),
+        _ => return None,
+    };
+    Some(key_code)
+}
+
+/// Decodes the modifier parameter - `1 + bitmask` of shift, alt, ctrl, super, hyper, meta, caps
+/// lock and num lock
+fn parse_modifier_param(param: u32) -> (KeyModifiers, KeyEventState) {
+    let bits = param.saturating_sub(1);
+    let bit = |n: u32| bits & (1 << n) != 0;
+
+    let modifiers = KeyModifiers {
+        none: bits.trailing_zeros() >= 6,
+        shift: bit(0),
+        alt: bit(1),
+        ctrl: bit(2),
+        super_key: bit(3),
+        hyper: bit(4),
+        meta: bit(5),
     };
+    let state = KeyEventState {
+        caps_lock: bit(6),
+        num_lock: bit(7),
+    };
+    (modifiers, state)
+}
 
-    KeyModifiers {
-        none: !shift && !alt && !ctrl,
-        shift,
-        alt,
-        ctrl,
+/// Decodes an operating system command - only the colour reports `10 ; rgb:r/g/b` and
+/// `11 ; rgb:r/g/b` are known
+fn parse_osc(command: &[u8]) -> Option<Event> {
+    let command = std::str::from_utf8(command).ok()?;
+    let (code, colour) = command.split_once(';')?;
+    let (red, green, blue) = parse_colour_spec(colour)?;
+    match code {
+        "10" => Some(Event::ForegroundColour(red, green, blue)),
+        "11" => Some(Event::BackgroundColour(red, green, blue)),
+        _ => None,
     }
 }
 
+/// Decodes a colour like `rgb:ffff/8080/0000`, with 1 to 4 hex digits per component, or `#ff8000`
+fn parse_colour_spec(spec: &str) -> Option<(u8, u8, u8)> {
+    let components: Vec<&str> = if let Some(rgb) = spec.strip_prefix("rgb:") {
+        rgb.split('/').collect()
+    } else {
+        let hex = spec.strip_prefix('#')?;
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        vec![&hex[0..2], &hex[2..4], &hex[4..6]]
+    };
+    let [red, green, blue] = components.as_slice() else {
+        return None;
+    };
+
+    // Scale every component to 8 bit
+    let scale = |component: &str| -> Option<u8> {
+        if component.is_empty() || component.len() > 4 {
+            return None;
+        }
+        let value = u32::from_str_radix(component, 16).ok()?;
+        let max = (1u32 << (4 * component.len())) - 1;
+        u8::try_from((value * 255 + max / 2) / max).ok()
+    };
+    Some((scale(red)?, scale(green)?, scale(blue)?))
+}
+
 fn parse_control_byte(byte: u8) -> Option<Event> {
     let (code, modifiers) = match byte {
         13 | 10 => (KeyCode::Enter, KeyModifiers::default()),
@@ -0,0 +1,65 @@
+use crate::utils::constants::ansi::{
+    QUERY_BACKGROUND_COLOUR, QUERY_CELL_PIXEL_SIZE, QUERY_CURSOR_POSITION, QUERY_FOREGROUND_COLOUR,
+    QUERY_WINDOW_PIXEL_SIZE,
+};
+
+use super::Event;
+
+/// A question to the terminal, answered with an event
+///
+/// Send it with `Talos::send_query` to receive the answer from `poll_input`, or with
+/// `Talos::query` to wait for the answer. Not every terminal answers every query.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use talos::{Talos, input::{Event, Query}};
+///
+/// let mut talos = Talos::builder().build().unwrap();
+/// let answer = talos.query(Query::BackgroundColour, Duration::from_millis(100)).unwrap();
+/// let dark_theme = match answer {
+///     Some(Event::BackgroundColour(red, green, blue)) => {
+///         u32::from(red) * 299 + u32::from(green) * 587 + u32::from(blue) * 114 < 128_000
+///     }
+///     // The terminal did not answer - guess
+///     _ => true,
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Query {
+    /// The position of the cursor, answered with `Event::CursorPosition`
+    CursorPosition,
+    /// The default foreground colour, answered with `Event::ForegroundColour`
+    ForegroundColour,
+    /// The default background colour, answered with `Event::BackgroundColour`
+    BackgroundColour,
+    /// The size of the text area in pixels, answered with `Event::WindowPixelSize`
+    WindowPixelSize,
+    /// The size of a cell in pixels, answered with `Event::CellPixelSize`
+    CellPixelSize,
+}
+
+impl Query {
+    /// The escape sequence asking the terminal
+    pub(crate) fn sequence(self) -> &'static str {
+        match self {
+            Query::CursorPosition => QUERY_CURSOR_POSITION,
+            Query::ForegroundColour => QUERY_FOREGROUND_COLOUR,
+            Query::BackgroundColour => QUERY_BACKGROUND_COLOUR,
+            Query::WindowPixelSize => QUERY_WINDOW_PIXEL_SIZE,
+            Query::CellPixelSize => QUERY_CELL_PIXEL_SIZE,
+        }
+    }
+
+    /// Returns whether `event` is the answer to this query
+    pub(crate) fn is_answered_by(self, event: &Event) -> bool {
+        matches!(
+            (self, event),
+            (Query::CursorPosition, Event::CursorPosition { .. })
+                | (Query::ForegroundColour, Event::ForegroundColour(..))
+                | (Query::BackgroundColour, Event::BackgroundColour(..))
+                | (Query::WindowPixelSize, Event::WindowPixelSize { .. })
+                | (Query::CellPixelSize, Event::CellPixelSize { .. })
+        )
+    }
+}
@@ -3,13 +3,18 @@
 #![warn(clippy::pedantic)]
 #![warn(clippy::all)]
 use std::io::Write;
+use std::time::{Duration, Instant};
 
+use input::GestureDetector;
 use input::Parser;
+use input::Query;
 use input::poll_input_bytes;
-use ui::render::{CCell, Style};
+use ui::render::{CCell, CursorShape, Style};
 use utils::constants::ansi::CLEAR_ALL;
+use utils::constants::ansi::CLEAR_BELOW;
 use utils::constants::ansi::TO_TOP_LEFT;
 use utils::constants::ansi::{BEGIN_SYNC_UPDATE, END_SYNC_UPDATE};
+use utils::constants::ansi::{HIDE_CURSOR, SHOW_CURSOR};
 use utils::write_all_bytes;
 
 mod builder;
@@ -20,14 +25,23 @@ pub use error::{TalosError, TalosResult};
 /// Style and Layout Atlases
 pub mod atlases;
 
-use crate::backend::TerminalIO;
+use crate::backend::Backend;
+use crate::backend::Capabilities;
+use crate::backend::ColourDepth;
+use crate::backend::sys::check_continue;
 use crate::backend::sys::check_resize;
+use crate::backend::sys::check_suspend;
 use crate::backend::sys::check_terminate;
+use crate::backend::sys::signal_pending;
+use crate::backend::sys::suspend_process;
 use crate::input::Event;
 use crate::ui::render::Canvas;
+use crate::utils::move_inline_cursor;
 use crate::utils::move_render_cursor;
+use crate::utils::set_cursor_shape;
 
-mod backend;
+/// Backends
+pub mod backend;
 mod content;
 mod ui;
 
@@ -53,17 +67,113 @@ type Height = u16;
 /// ```
 ///
 /// For more information on building the struct, see [`TalosBuilder`](struct.TalosBuilder.html).
+#[allow(clippy::struct_excessive_bools)]
 pub struct Talos {
-    terminal: TerminalIO,
+    backend: Box<dyn Backend>,
     canvas: Canvas,
     thoth: thoth::Thoth,
     // Terminal Size
-    /// Width, Height
+    /// Width, Height - of the viewport
     size: (Width, Height),
+    viewport: Viewport,
+    capabilities: Capabilities,
+    /// Row of the cursor inside of an inline viewport - `None` if not rendering inline
+    inline_cursor: Option<u16>,
+    // Terminal cursor
+    cursor: Option<(u16, u16)>,
+    cursor_shape: CursorShape,
+    /// The shape last written to the terminal
+    applied_cursor_shape: CursorShape,
+    /// Whether `Talos` showed the cursor, and has to hide it again
+    cursor_shown: bool,
     previous_buffer: Vec<CCell>,
     output_buffer: Vec<u8>,
     // Input-Parser
     parser: Parser,
+    /// Events generated by `Talos` itself, returned by the next `poll_input`
+    pending_events: Vec<Event>,
+    // Signals
+    termination_policy: TerminationPolicy,
+    /// Whether `TalosError::Terminated` was returned - every following call returns it as well
+    terminated: bool,
+    /// Whether the terminal is handed back, between `suspend` and `resume`
+    suspended: bool,
+    /// Whether `Talos` suspended itself on `SIGTSTP`, and has to resume on `SIGCONT`
+    resume_on_continue: bool,
+}
+
+/// The area of the terminal `Talos` renders into
+///
+/// Set with [`TalosBuilder::with_viewport`](struct.TalosBuilder.html#method.with_viewport).
+///
+/// # Example
+/// ```rust,no_run
+/// use talos::{Talos, Viewport};
+///
+/// // Render a 3 line progress display below the shell prompt
+/// let talos = Talos::builder().with_viewport(Viewport::Inline(3)).build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Viewport {
+    /// The whole terminal - this is the default
+    #[default]
+    Fullscreen,
+    /// The given number of lines, starting at the line of the cursor
+    ///
+    /// The alternate screen is not used, and the rendered lines stay in the scrollback once
+    /// `Talos` is dropped. Lines are added to the bottom of the terminal if there are not enough
+    /// below the cursor. The height is capped at the height of the terminal, the width always
+    /// spans the whole terminal.
+    Inline(u16),
+}
+
+impl Viewport {
+    /// Returns the size of the viewport inside of a terminal of the given size
+    fn size(self, (width, height): (Width, Height)) -> (Width, Height) {
+        match self {
+            Viewport::Fullscreen => (width, height),
+            Viewport::Inline(lines) => (width, lines.min(height)),
+        }
+    }
+}
+
+/// What `Talos` does when the process is asked to terminate (`SIGTERM` or `SIGINT`)
+///
+/// Set with [`TalosBuilder::with_termination_policy`](struct.TalosBuilder.html#method.with_termination_policy).
+/// The policy is applied by the next call to `present`, `poll_input` or `wait_input`.
+///
+/// # Example
+/// ```rust,no_run
+/// use talos::{Talos, TerminationPolicy};
+///
+/// let talos = Talos::builder()
+///     .with_termination_policy(TerminationPolicy::Exit(130))
+///     .build();
+/// ```
+pub enum TerminationPolicy {
+    /// Restore the terminal and exit the process with the given exit code
+    ///
+    /// This is the default, with an exit code of `0`.
+    Exit(i32),
+    /// Restore the terminal and return `TalosError::Terminated`
+    ///
+    /// Every following call to `present`, `poll_input` and `wait_input` returns the error as well.
+    Error,
+    /// Report every termination signal as `Event::Terminate`
+    ///
+    /// The terminal is left as is - the application is responsible for shutting down.
+    Event,
+    /// Invoke the callback for every termination signal
+    ///
+    /// The terminal is left as is - the callback can e.g. save state and set a flag for the main
+    /// loop to stop.
+    Callback(Box<dyn FnMut()>),
+}
+
+impl Default for TerminationPolicy {
+    fn default() -> Self {
+        TerminationPolicy::Exit(0)
+    }
 }
 
 /// Return type of `Talos::present`
@@ -149,10 +259,11 @@ impl Talos {
     /// Returns whether the terminal was resized.
     /// If the terminal was resized, `present` will not draw anything to the terminal.
     /// Returns `Ok(Present::Resized)` if the terminal was resized.
+    /// After resuming from a suspend (`Ctrl+Z`), the frame is drawn in full.
     /// Returns `Ok(Present::Presented)` if the terminal was not resized.
     ///
     /// # Errors
-    /// Returns an error if the terminal was terminated.
+    /// Returns an error if the terminal was terminated, depending on the `TerminationPolicy`.
     ///
     /// # Example
     /// ```rust,no_run
@@ -169,15 +280,21 @@ impl Talos {
 
         self.output_buffer.clear();
 
-        write_all_bytes(&mut self.output_buffer, TO_TOP_LEFT.as_bytes())?;
+        // Inline viewports only ever move the cursor relative to where it is
+        let mut inline_cursor = self.inline_cursor;
+        if inline_cursor.is_none() {
+            write_all_bytes(&mut self.output_buffer, TO_TOP_LEFT.as_bytes())?;
+        }
 
         // Removing the next line will cause some weird side effects (Bleeding the `selected` style of
         // `List` to previous elements for example);
         // Doing this is not ideal (performance-wise) but it works
         Style::default().generate(&mut self.output_buffer);
 
-        let mut prev_x_cell: u16 = u16::MAX;
+        // The last cell written - the cursor position is only known right after it
+        let mut prev_cell: Option<(u16, u16)> = None;
         let mut current_terminal_style = Style::default();
+        let colour_depth = self.capabilities.colour_depth;
 
         for y in 0..self.size.1 {
             for x in 0..self.size.0 {
@@ -186,23 +303,28 @@ impl Talos {
                 if self.canvas.buffer[buffer_index] != self.previous_buffer[buffer_index] {
                     let ccell = self.canvas.get_ccell(x, y);
 
-                    if x.wrapping_sub(prev_x_cell) != 1 {
-                        move_render_cursor(&mut self.output_buffer, x, y)?;
+                    // Only the cell right after the last one on the same row needs no move
+                    if prev_cell != Some((x.wrapping_sub(1), y)) {
+                        if let Some(row) = inline_cursor.as_mut() {
+                            move_inline_cursor(&mut self.output_buffer, *row, x, y)?;
+                            *row = y;
+                        } else {
+                            move_render_cursor(&mut self.output_buffer, x, y)?;
+                        }
                     }
 
                     // Only generate style if it differs from the current terminal style
-                    if ccell.style != current_terminal_style {
-                        ccell
-                            .style
-                            .generate_diff(current_terminal_style, &mut self.output_buffer);
-                        current_terminal_style = ccell.style;
+                    let style = ccell.style.downgrade(colour_depth);
+                    if style != current_terminal_style {
+                        style.generate_diff(current_terminal_style, &mut self.output_buffer);
+                        current_terminal_style = style;
                     }
 
                     write_all_bytes(
                         &mut self.output_buffer,
                         ccell.char.as_str().as_bytes(),
                     )?;
-                    prev_x_cell = x;
+                    prev_cell = Some((x, y));
                 }
             }
         }
@@ -213,14 +335,18 @@ impl Talos {
             return Ok(Present::Presented);
         }
 
-        self.terminal
-            .stdout()
-            .write_all(BEGIN_SYNC_UPDATE.as_bytes())?;
-        self.terminal.stdout().write_all(&self.output_buffer)?;
-        self.terminal
-            .stdout()
-            .write_all(END_SYNC_UPDATE.as_bytes())?;
-        self.terminal.stdout().flush()?;
+        self.place_cursor(&mut inline_cursor)?;
+
+        let sync = self.capabilities.synchronized_update;
+        if sync {
+            self.backend.write_all(BEGIN_SYNC_UPDATE.as_bytes())?;
+        }
+        self.backend.write_all(&self.output_buffer)?;
+        if sync {
+            self.backend.write_all(END_SYNC_UPDATE.as_bytes())?;
+        }
+        self.backend.flush()?;
+        self.inline_cursor = inline_cursor;
 
         // Pointer swapping of the buffers
         std::mem::swap(&mut self.previous_buffer, &mut self.canvas.buffer);
@@ -228,6 +354,82 @@ impl Talos {
         Ok(Present::Presented)
     }
 
+    /// Returns what the terminal supports
+    ///
+    /// Detected once while building `Talos`. See
+    /// [`Capabilities`](backend/struct.Capabilities.html).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::Talos;
+    ///
+    /// let talos = Talos::builder().build().unwrap();
+    /// if let Some(version) = &talos.capabilities().terminal_version {
+    ///     println!("Running in {version}");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Shows the terminal cursor at the given position, or hides it again
+    ///
+    /// The position is relative to the canvas, and applied by every following `present` - after
+    /// drawing the frame. `None` and positions outside of the canvas hide the cursor, if it was
+    /// shown by this method.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::Talos;
+    ///
+    /// let mut talos = Talos::builder().build().unwrap();
+    /// // Place the cursor behind the text typed so far
+    /// talos.set_cursor(Some((12, 3)));
+    /// talos.present().unwrap();
+    /// ```
+    pub fn set_cursor(&mut self, position: Option<(u16, u16)>) {
+        self.cursor = position;
+    }
+
+    /// Sets the shape of the terminal cursor, applied by the next `present`
+    ///
+    /// The shape is reset to the default of the terminal once `Talos` is dropped or suspended.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::{Talos, render::CursorShape};
+    ///
+    /// let mut talos = Talos::builder().build().unwrap();
+    /// talos.set_cursor_shape(CursorShape::SteadyBar);
+    /// ```
+    pub fn set_cursor_shape(&mut self, shape: CursorShape) {
+        self.cursor_shape = shape;
+    }
+
+    /// Sets the colour depth colours are rendered in
+    ///
+    /// Colours the terminal cannot display are replaced by the closest ones it can, see
+    /// [`Style::downgrade`](render/struct.Style.html#method.downgrade). By default, the colour
+    /// depth is detected while building `Talos`.
+    ///
+    /// The canvas is cleared, and the next `present` draws the whole frame.
+    ///
+    /// # Errors
+    /// Returns an error if the screen could not be cleared.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::{Talos, backend::ColourDepth};
+    ///
+    /// let mut talos = Talos::builder().build().unwrap();
+    /// talos.set_colour_depth(ColourDepth::Ansi16).unwrap();
+    /// ```
+    pub fn set_colour_depth(&mut self, colour_depth: ColourDepth) -> TalosResult<()> {
+        self.capabilities.colour_depth = colour_depth;
+        self.reset_screen()
+    }
+
     /// Returns the thoth engine
     ///
     /// # Example
@@ -262,8 +464,10 @@ impl Talos {
     /// Eagerly evaluates all bytes read, and returns an `Event::Unknown` if
     /// the bytes cannot be parsed.
     ///
+    /// Resizes of the terminal are reported as `Event::Resize`, before any input.
+    ///
     /// # Errors
-    /// Returns an error if the terminal was terminated.
+    /// Returns an error if the terminal was terminated, depending on the `TerminationPolicy`.
     ///
     /// # Example
     /// ```rust,no_run
@@ -296,9 +500,10 @@ impl Talos {
         let _ = self.handle_signals()?;
 
         self.parser.event_buffer.clear();
+        self.parser.event_buffer.append(&mut self.pending_events);
 
         if let Some(bytes) = poll_input_bytes(
-            &mut self.terminal.stdin(),
+            &mut self.backend,
             &mut self.parser.poll_input_buffer,
             self.parser.max_poll_input_buffer,
             self.parser.buffer_linear_growth_step,
@@ -309,41 +514,482 @@ impl Talos {
         } else {
             self.parser.parser.flush(&mut self.parser.event_buffer);
         }
+        self.backend.poll_events(&mut self.parser.event_buffer);
+        if let Some(gestures) = &mut self.parser.gestures {
+            gestures.process(&mut self.parser.event_buffer);
+        }
 
         Ok(Some(self.parser.event_buffer.as_slice()))
     }
 
+    /// Waits for input, then returns all input events since the last call.
+    ///
+    /// Blocks until input arrives, the terminal is resized or the `timeout` elapsed, whichever
+    /// comes first. A `timeout` of `None` waits until input arrives or the terminal is resized.
+    ///
+    /// Use this instead of `poll_input` and sleeping, to not burn CPU while idle and to react to
+    /// input without delay.
+    ///
+    /// # Errors
+    /// Returns an error if the terminal was terminated, depending on the `TerminationPolicy`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use talos::Talos;
+    /// use talos::input::{Event, KeyEvent, KeyCode};
+    ///
+    /// let mut talos = Talos::builder().build().unwrap();
+    /// let mut run = true;
+    /// while run {
+    ///     if let Some(events) = talos.wait_input(Some(Duration::from_millis(16))).unwrap() {
+    ///         for event in events {
+    ///             if let Event::KeyEvent(KeyEvent { code: KeyCode::Char('q'), .. }) = event {
+    ///                 run = false;
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn wait_input(&mut self, timeout: Option<Duration>) -> TalosResult<Option<&[Event]>> {
+        // Events queued by `Talos` itself, e.g. input read while waiting for a `query`, are ready
+        if !signal_pending() && self.pending_events.is_empty() {
+            // A pending escape sequence is flushed once its timeout elapsed, and a long press is
+            // recognised once the button was held long enough - don't wait longer
+            let pending_timeout = self
+                .parser
+                .parser
+                .is_pending()
+                .then(|| self.parser.parser.pending_timeout());
+            let gesture_timeout = self
+                .parser
+                .gestures
+                .as_ref()
+                .and_then(GestureDetector::timeout);
+            let timeout = [timeout, pending_timeout, gesture_timeout]
+                .into_iter()
+                .flatten()
+                .min();
+            self.backend.wait_for_input(timeout)?;
+        }
+        self.poll_input()
+    }
+
+    /// Asks the terminal a question - the answer is returned by `poll_input` as an event
+    ///
+    /// # Errors
+    /// Returns an error if writing to the terminal fails.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::{Talos, input::{Event, Query}};
+    ///
+    /// let mut talos = Talos::builder().build().unwrap();
+    /// talos.send_query(Query::CellPixelSize).unwrap();
+    /// // ... later
+    /// for event in talos.poll_input().unwrap().unwrap_or_default() {
+    ///     if let Event::CellPixelSize { width, height } = event {
+    ///         // Scale images to the cell size
+    ///     }
+    /// }
+    /// ```
+    pub fn send_query(&mut self, query: Query) -> TalosResult<()> {
+        if query == Query::CursorPosition {
+            self.parser.parser.expect_cursor_position();
+        }
+        self.backend.write_all(query.sequence().as_bytes())?;
+        self.backend.flush()?;
+        Ok(())
+    }
+
+    /// Asks the terminal a question and waits up to `timeout` for the answer
+    ///
+    /// Returns `None` if the terminal did not answer in time - not every terminal answers every
+    /// query. Input arriving in the meantime is returned by the next `poll_input`.
+    ///
+    /// # Errors
+    /// Returns an error if writing to or reading from the terminal fails.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use talos::{Talos, input::{Event, Query}};
+    ///
+    /// let mut talos = Talos::builder().build().unwrap();
+    /// let timeout = Duration::from_millis(100);
+    /// if let Some(Event::CursorPosition { column, row }) =
+    ///     talos.query(Query::CursorPosition, timeout).unwrap()
+    /// {
+    ///     println!("The cursor is at {column}, {row}");
+    /// }
+    /// ```
+    pub fn query(&mut self, query: Query, timeout: Duration) -> TalosResult<Option<Event>> {
+        self.send_query(query)?;
+
+        let deadline = Instant::now() + timeout;
+        let mut events = Vec::new();
+        let answer = loop {
+            if let Some(bytes) = poll_input_bytes(
+                &mut self.backend,
+                &mut self.parser.poll_input_buffer,
+                self.parser.max_poll_input_buffer,
+                self.parser.buffer_linear_growth_step,
+            )? {
+                self.parser.parser.parse(bytes, &mut events)?;
+            }
+            if let Some(index) = events.iter().position(|event| query.is_answered_by(event)) {
+                break Some(events.remove(index));
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                // A late answer would be taken for a key press otherwise
+                if query == Query::CursorPosition {
+                    self.parser.parser.cancel_cursor_position();
+                }
+                break None;
+            }
+            self.backend.wait_for_input(Some(deadline - now))?;
+        };
+
+        self.pending_events.append(&mut events);
+        Ok(answer)
+    }
+
+    /// Hands the terminal back, e.g. to run an external program like `$EDITOR`
+    ///
+    /// Leaves the alternate screen, disables mouse reporting, shows the cursor and restores the
+    /// original terminal mode. An inline viewport is left in the scrollback, with the cursor
+    /// placed below it. Call `resume` to take the terminal over again.
+    /// Nothing should be presented while suspended. Does nothing if already suspended.
+    ///
+    /// # Errors
+    /// Returns an error if the terminal could not be restored.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use std::process::Command;
+    /// use talos::Talos;
+    ///
+    /// let mut talos = Talos::builder().build().unwrap();
+    /// talos.suspend().unwrap();
+    /// let _ = Command::new("vi").status();
+    /// talos.resume().unwrap();
+    /// ```
+    pub fn suspend(&mut self) -> TalosResult<()> {
+        if self.suspended {
+            return Ok(());
+        }
+        self.release_cursor()?;
+        self.leave_viewport()?;
+        self.backend.restore()?;
+        self.suspended = true;
+        Ok(())
+    }
+
+    /// Takes the terminal over again after `suspend`
+    ///
+    /// Re-enters raw mode and the alternate screen, re-enables mouse reporting and clears the
+    /// screen, so that the next `present` draws the whole frame.
+    /// An inline viewport is reserved anew, starting at the line of the cursor.
+    /// If the terminal was resized in the meantime, an `Event::Resize` is queued.
+    /// Does nothing if not suspended.
+    ///
+    /// # Errors
+    /// Returns an error if the terminal could not be set up again.
+    pub fn resume(&mut self) -> TalosResult<()> {
+        if !self.suspended {
+            return Ok(());
+        }
+        self.backend.enter()?;
+        self.backend.enable_modes()?;
+        self.suspended = false;
+        // Resizes while suspended are picked up right here - not a second time by `handle_signals`
+        check_resize();
+        self.backend.poll_resize();
+        self.refresh_size()?;
+        self.reserve_viewport()?;
+        self.reset_screen()
+    }
+
+    /// Runs `f` while the terminal is suspended, then resumes
+    ///
+    /// See `suspend` and `resume`.
+    ///
+    /// # Errors
+    /// Returns an error if the terminal could not be restored or set up again.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use std::process::Command;
+    /// use talos::Talos;
+    ///
+    /// let mut talos = Talos::builder().build().unwrap();
+    /// let status = talos.with_suspended(|| Command::new("less").arg("README.md").status()).unwrap();
+    /// ```
+    pub fn with_suspended<F, R>(&mut self, f: F) -> TalosResult<R>
+    where
+        F: FnOnce() -> R,
+    {
+        self.suspend()?;
+        let out = f();
+        self.resume()?;
+        Ok(out)
+    }
+
+    /// Prints lines above an inline viewport, into the scrollback of the terminal
+    ///
+    /// Every entry is printed on its own line, line breaks within an entry start new lines.
+    /// The viewport moves down to make room and is redrawn in full by the next `present`.
+    ///
+    /// # Errors
+    /// Returns `TalosError::InvalidState` if `Talos` is not rendering into an inline viewport, or
+    /// an error if writing to the terminal fails.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use talos::Talos;
+    ///
+    /// let mut talos = Talos::builder().with_inline_viewport(1).build().unwrap();
+    /// for step in ["fetch", "build", "deploy"] {
+    ///     // ... render the progress of `step` and present it ...
+    ///     talos.insert_before(&[format!("finished {step}")]).unwrap();
+    /// }
+    /// ```
+    pub fn insert_before<S: AsRef<str>>(&mut self, lines: &[S]) -> TalosResult<()> {
+        let Some(row) = self.inline_cursor else {
+            return Err(TalosError::InvalidState);
+        };
+
+        let sync = self.capabilities.synchronized_update;
+        let mut out = Vec::new();
+        if sync {
+            write_all_bytes(&mut out, BEGIN_SYNC_UPDATE.as_bytes())?;
+        }
+        // The lines replace the viewport, which is then reserved again below them
+        move_inline_cursor(&mut out, row, 0, 0)?;
+        write_all_bytes(&mut out, CLEAR_BELOW.as_bytes())?;
+        Style::default().generate(&mut out);
+        for line in lines {
+            for part in line.as_ref().split('\n') {
+                write_all_bytes(&mut out, part.trim_end_matches('\r').as_bytes())?;
+                write_all_bytes(&mut out, b"\r\n")?;
+            }
+        }
+        self.backend.write_all(&out)?;
+
+        self.reserve_viewport()?;
+        if sync {
+            self.backend.write_all(END_SYNC_UPDATE.as_bytes())?;
+            self.backend.flush()?;
+        }
+
+        // The reserved lines are empty - draw everything again
+        self.previous_buffer.fill(CCell::default());
+        Ok(())
+    }
+
     /// Handles signals from the OS
     ///
     /// Returns `true` whether the terminal was resized
     /// Returns `false` if the terminal was not resized
-    /// ENDS THE PROCESS if the terminate signal was received
+    /// ENDS THE PROCESS if the terminate signal was received, and the `TerminationPolicy` is `Exit`
     ///
-    /// If the terminal was terminated, the terminal is restored and the process exits.
+    /// Resizes (and terminations, if the policy is `Event`) are queued as events for the next
+    /// `poll_input`.
     ///
     /// # Errors
-    /// Returns an error if internal I/O errors occur
+    /// Returns an error if internal I/O errors occur, or `TalosError::Terminated` if the terminate
+    /// signal was received and the `TerminationPolicy` is `Error`
     fn handle_signals(&mut self) -> TalosResult<bool> {
+        if self.terminated {
+            return Err(TalosError::Terminated);
+        }
+
         if check_terminate() {
-            // We need to shut down now - No state will be saved, just restore the terminal
-            self.terminal.restore()?;
-            std::process::exit(0);
+            match &mut self.termination_policy {
+                TerminationPolicy::Exit(code) => {
+                    let code = *code;
+                    // We need to shut down now - No state will be saved, just restore the terminal
+                    self.suspend()?;
+                    std::process::exit(code);
+                }
+                TerminationPolicy::Error => {
+                    self.terminated = true;
+                    self.suspend()?;
+                    return Err(TalosError::Terminated);
+                }
+                TerminationPolicy::Event => self.pending_events.push(Event::Terminate),
+                TerminationPolicy::Callback(callback) => callback(),
+            }
         }
 
-        if check_resize() {
-            let (rows, cols) = self.terminal.size()?;
-            self.size = (cols, rows);
+        if check_suspend() {
+            // Hand the terminal back to the shell before actually stopping - unless the
+            // application already did
+            self.resume_on_continue = !self.suspended;
+            self.suspend()?;
+            suspend_process()?;
+            // Resumed - `SIGCONT` was delivered, so the check below picks it up
+        }
 
-            self.canvas = Canvas::new(self.size.0, self.size.1);
-            let len = (self.size.0 as usize) * (self.size.1 as usize);
-            self.previous_buffer = vec![CCell::default(); len];
-            self.output_buffer.clear();
-            self.output_buffer.reserve(len * 10);
-            write_all_bytes(&mut self.terminal.stdout(), CLEAR_ALL.as_bytes())?;
-            self.terminal.stdout().flush()?;
+        if check_continue() {
+            if std::mem::take(&mut self.resume_on_continue) {
+                self.resume()?;
+            } else if !self.suspended {
+                // Stopped by someone else (`SIGSTOP`) - the terminal is still set up, but whatever
+                // happened while we were stopped, the screen contents are gone
+                self.reset_screen()?;
+            }
+        }
+
+        let signal_resized = check_resize();
+        let backend_resized = self.backend.poll_resize();
+        if signal_resized || backend_resized {
+            self.size = self.viewport.size(self.backend.size()?);
+            self.pending_events.push(Event::Resize {
+                width: self.size.0,
+                height: self.size.1,
+            });
+            // While suspended, the terminal is not ours - `resume` sets the screen up again
+            if !self.suspended {
+                self.reset_screen()?;
+                // The terminal may have reflowed or scrolled - an inline viewport needs its lines
+                // reserved anew
+                self.reserve_viewport()?;
+            }
             return Ok(true);
         }
 
         Ok(false)
     }
+
+    /// Moves the cursor to where it was set to be, and applies its visibility and shape
+    fn place_cursor(&mut self, inline_cursor: &mut Option<u16>) -> TalosResult<()> {
+        let position = self
+            .cursor
+            .filter(|&(x, y)| x < self.size.0 && y < self.size.1);
+
+        if let Some((x, y)) = position {
+            if let Some(row) = inline_cursor.as_mut() {
+                move_inline_cursor(&mut self.output_buffer, *row, x, y)?;
+                *row = y;
+            } else {
+                move_render_cursor(&mut self.output_buffer, x, y)?;
+            }
+            if !self.cursor_shown {
+                write_all_bytes(&mut self.output_buffer, SHOW_CURSOR.as_bytes())?;
+                self.cursor_shown = true;
+            }
+        } else if self.cursor_shown {
+            write_all_bytes(&mut self.output_buffer, HIDE_CURSOR.as_bytes())?;
+            self.cursor_shown = false;
+        }
+
+        if self.cursor_shape != self.applied_cursor_shape {
+            set_cursor_shape(&mut self.output_buffer, self.cursor_shape.decode())?;
+            self.applied_cursor_shape = self.cursor_shape;
+        }
+        Ok(())
+    }
+
+    /// Resets the shape of the cursor to the default of the terminal
+    ///
+    /// Its visibility is restored by the backend, and set up again by `enter`.
+    fn release_cursor(&mut self) -> TalosResult<()> {
+        self.cursor_shown = false;
+        if self.applied_cursor_shape != CursorShape::Default {
+            let mut reset = Vec::new();
+            set_cursor_shape(&mut reset, CursorShape::Default.decode())?;
+            self.backend.write_all(&reset)?;
+            self.backend.flush()?;
+            self.applied_cursor_shape = CursorShape::Default;
+        }
+        Ok(())
+    }
+
+    /// Reads the size from the backend, queueing an `Event::Resize` if it changed
+    fn refresh_size(&mut self) -> TalosResult<()> {
+        let old_size = self.size;
+        self.size = self.viewport.size(self.backend.size()?);
+        if self.size != old_size {
+            self.pending_events.push(Event::Resize {
+                width: self.size.0,
+                height: self.size.1,
+            });
+        }
+        Ok(())
+    }
+
+    /// Recreates the previous buffer (and the canvas, if the size changed) and clears the screen,
+    /// forcing the next frame to be drawn in full
+    fn reset_screen(&mut self) -> TalosResult<()> {
+        // Keep the frame drawn so far if it still fits
+        let area = self.canvas.size_rect();
+        if (area.width, area.height) != self.size {
+            self.canvas = Canvas::new(self.size.0, self.size.1);
+        }
+        let len = (self.size.0 as usize) * (self.size.1 as usize);
+        self.previous_buffer = vec![CCell::default(); len];
+        self.output_buffer.clear();
+        self.output_buffer.reserve(len * 10);
+
+        if let Some(row) = self.inline_cursor {
+            let mut clear = Vec::new();
+            move_inline_cursor(&mut clear, row, 0, 0)?;
+            write_all_bytes(&mut clear, CLEAR_BELOW.as_bytes())?;
+            self.backend.write_all(&clear)?;
+            self.inline_cursor = Some(0);
+        } else {
+            write_all_bytes(&mut self.backend, CLEAR_ALL.as_bytes())?;
+        }
+        self.backend.flush()?;
+        Ok(())
+    }
+
+    /// Reserves the lines of an inline viewport, starting at the line of the cursor
+    ///
+    /// Does nothing for a fullscreen viewport.
+    fn reserve_viewport(&mut self) -> TalosResult<()> {
+        if self.viewport == Viewport::Fullscreen {
+            return Ok(());
+        }
+
+        // Line feeds scroll the terminal if there are not enough lines below the cursor
+        let lines = self.size.1.saturating_sub(1);
+        let mut reserve = vec![b'\n'; lines as usize];
+        move_inline_cursor(&mut reserve, lines, 0, 0)?;
+        self.backend.write_all(&reserve)?;
+        self.backend.flush()?;
+        self.inline_cursor = Some(0);
+        Ok(())
+    }
+
+    /// Places the cursor on the line below an inline viewport, leaving it in the scrollback
+    ///
+    /// Does nothing for a fullscreen viewport, or if the viewport was already left.
+    fn leave_viewport(&mut self) -> TalosResult<()> {
+        let Some(row) = self.inline_cursor.take() else {
+            return Ok(());
+        };
+
+        let mut leave = Vec::new();
+        move_inline_cursor(&mut leave, row, 0, self.size.1.saturating_sub(1))?;
+        // The shell should not inherit the last style
+        Style::default().generate(&mut leave);
+        write_all_bytes(&mut leave, b"\n")?;
+        self.backend.write_all(&leave)?;
+        self.backend.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for Talos {
+    fn drop(&mut self) {
+        // The backend restores the terminal itself - only what `Talos` changed has to be undone
+        if self.suspended {
+            return;
+        }
+        let _ = self.release_cursor();
+        let _ = self.leave_viewport();
+    }
 }
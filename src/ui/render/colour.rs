@@ -1,3 +1,5 @@
+use crate::backend::ColourDepth;
+
 pub const FG_PRE_DIGIT_NORMAL: u8 = 3;
 pub const FG_PRE_DIGIT_BRIGHT: u8 = 9;
 
@@ -22,6 +24,135 @@ pub enum Colour {
     Extended(Extended),
 }
 
+impl Colour {
+    /// Returns the closest colour a terminal with the given colour depth can display
+    ///
+    /// RGB colours are mapped to the nearest entry of the 256 colour palette, both RGB and palette
+    /// colours to the nearest of the 16 ANSI colours. Returns `None` for
+    /// `ColourDepth::Monochrome`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use talos::{backend::ColourDepth, render::{Colour, ColourMode, Extended, Normal, TrueColour}};
+    ///
+    /// let orange = Colour::Extended(Extended::TrueColour(TrueColour::RGB(255, 135, 0)));
+    /// assert_eq!(
+    ///     orange.downgrade(ColourDepth::Ansi256),
+    ///     Some(Colour::Extended(Extended::ColourMode(ColourMode::RgbBit(208))))
+    /// );
+    /// assert_eq!(orange.downgrade(ColourDepth::Ansi16), Some(Colour::Normal(Normal::Yellow)));
+    /// assert_eq!(orange.downgrade(ColourDepth::Monochrome), None);
+    /// ```
+    #[must_use]
+    pub fn downgrade(self, depth: ColourDepth) -> Option<Colour> {
+        match (depth, self) {
+            (ColourDepth::Monochrome, _) => None,
+            (ColourDepth::TrueColour, _)
+            | (_, Colour::Normal(_) | Colour::Bright(_))
+            | (ColourDepth::Ansi256, Colour::Extended(Extended::ColourMode(_))) => Some(self),
+            (ColourDepth::Ansi256, Colour::Extended(Extended::TrueColour(tc))) => {
+                Some(Colour::Extended(Extended::ColourMode(ColourMode::RgbBit(
+                    nearest_256(tc.decode()),
+                ))))
+            }
+            (ColourDepth::Ansi16, Colour::Extended(Extended::ColourMode(cm))) => {
+                let index = cm.decode();
+                if index < 16 {
+                    Some(ansi_16(index))
+                } else {
+                    Some(ansi_16(nearest_16(palette_rgb(index))))
+                }
+            }
+            (ColourDepth::Ansi16, Colour::Extended(Extended::TrueColour(tc))) => {
+                Some(ansi_16(nearest_16(tc.decode())))
+            }
+        }
+    }
+}
+
+/// The 16 ANSI colours, as the default xterm palette displays them
+const ANSI_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The levels of every channel of the 6x6x6 colour cube of the 256 colour palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// Returns the RGB value of an entry of the 256 colour palette
+fn palette_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_PALETTE[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+/// Returns the index of the closest colour of the 256 colour palette - ignoring the 16 ANSI
+/// colours, as terminals tend to change those
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let nearest_level = |v: u8| {
+        (0..6u8)
+            .min_by_key(|&i| CUBE_LEVELS[i as usize].abs_diff(v))
+            .unwrap_or_default()
+    };
+    let cube = 16 + 36 * nearest_level(rgb.0) + 6 * nearest_level(rgb.1) + nearest_level(rgb.2);
+
+    let average = (u16::from(rgb.0) + u16::from(rgb.1) + u16::from(rgb.2)) / 3;
+    #[allow(clippy::cast_possible_truncation)]
+    let grey = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    if distance(palette_rgb(grey), rgb) < distance(palette_rgb(cube), rgb) {
+        grey
+    } else {
+        cube
+    }
+}
+
+/// Returns the index of the closest of the 16 ANSI colours
+fn nearest_16(rgb: (u8, u8, u8)) -> u8 {
+    (0..16u8)
+        .min_by_key(|&i| distance(ANSI_PALETTE[i as usize], rgb))
+        .unwrap_or_default()
+}
+
+fn ansi_16(index: u8) -> Colour {
+    if index < 8 {
+        Colour::Normal(Normal::from_index(index).unwrap_or(Normal::Black))
+    } else {
+        Colour::Bright(Bright::from_index(index - 8).unwrap_or(Bright::Black))
+    }
+}
+
 /// Normal 8 colours
 ///
 /// # Example
@@ -68,6 +199,21 @@ impl Normal {
             Normal::White => 7,
         }
     }
+
+    /// Returns the colour for a terminal index - the inverse of `decode`
+    pub(crate) fn from_index(index: u8) -> Option<Self> {
+        match index {
+            0 => Some(Normal::Black),
+            1 => Some(Normal::Red),
+            2 => Some(Normal::Green),
+            3 => Some(Normal::Yellow),
+            4 => Some(Normal::Blue),
+            5 => Some(Normal::Magenta),
+            6 => Some(Normal::Cyan),
+            7 => Some(Normal::White),
+            _ => None,
+        }
+    }
 }
 
 /// Bright 8 colours
@@ -114,6 +260,21 @@ impl Bright {
             Bright::White => 7,
         }
     }
+
+    /// Returns the colour for a terminal index - the inverse of `decode`
+    pub(crate) fn from_index(index: u8) -> Option<Self> {
+        match index {
+            0 => Some(Bright::Black),
+            1 => Some(Bright::Red),
+            2 => Some(Bright::Green),
+            3 => Some(Bright::Yellow),
+            4 => Some(Bright::Blue),
+            5 => Some(Bright::Magenta),
+            6 => Some(Bright::Cyan),
+            7 => Some(Bright::White),
+            _ => None,
+        }
+    }
 }
 
 /// More colours - All RGB colours
@@ -202,3 +363,51 @@ impl TrueColour {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_256() {
+        assert_eq!(nearest_256((0, 0, 0)), 16);
+        assert_eq!(nearest_256((255, 255, 255)), 231);
+        assert_eq!(nearest_256((255, 0, 0)), 196);
+        assert_eq!(nearest_256((128, 128, 128)), 244);
+        assert_eq!(nearest_256((95, 135, 175)), 67);
+        for index in 16..=255 {
+            assert_eq!(nearest_256(palette_rgb(index)), index);
+        }
+    }
+
+    #[test]
+    fn test_downgrade_to_16() {
+        let rgb = |r, g, b| Colour::Extended(Extended::TrueColour(TrueColour::RGB(r, g, b)));
+        let palette = |n| Colour::Extended(Extended::ColourMode(ColourMode::RgbBit(n)));
+
+        assert_eq!(
+            rgb(200, 10, 10).downgrade(ColourDepth::Ansi16),
+            Some(Colour::Normal(Normal::Red))
+        );
+        assert_eq!(
+            rgb(90, 90, 250).downgrade(ColourDepth::Ansi16),
+            Some(Colour::Bright(Bright::Blue))
+        );
+        assert_eq!(
+            palette(9).downgrade(ColourDepth::Ansi16),
+            Some(Colour::Bright(Bright::Red))
+        );
+        assert_eq!(
+            palette(46).downgrade(ColourDepth::Ansi16),
+            Some(Colour::Bright(Bright::Green))
+        );
+        assert_eq!(
+            Colour::Normal(Normal::Cyan).downgrade(ColourDepth::Ansi16),
+            Some(Colour::Normal(Normal::Cyan))
+        );
+        assert_eq!(
+            Colour::Normal(Normal::Cyan).downgrade(ColourDepth::Monochrome),
+            None
+        );
+    }
+}
@@ -0,0 +1,53 @@
+/// The shape of the terminal cursor
+///
+/// Set with `Talos::set_cursor_shape`, applied by the next `present`.
+///
+/// # Example
+/// ```rust,no_run
+/// use talos::{Talos, render::CursorShape};
+///
+/// let mut talos = Talos::builder().build().unwrap();
+/// talos.set_cursor_shape(CursorShape::BlinkingBar);
+/// talos.set_cursor(Some((4, 2)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorShape {
+    /// Whatever the user configured in their terminal
+    #[default]
+    Default,
+    /// A blinking block
+    BlinkingBlock,
+    /// A steady block
+    SteadyBlock,
+    /// A blinking underline
+    BlinkingUnderline,
+    /// A steady underline
+    SteadyUnderline,
+    /// A blinking vertical bar
+    BlinkingBar,
+    /// A steady vertical bar
+    SteadyBar,
+}
+
+impl CursorShape {
+    /// Returns the parameter of the `DECSCUSR` control sequence
+    ///
+    /// # Example
+    /// ```rust
+    /// use talos::render::CursorShape;
+    ///
+    /// assert_eq!(CursorShape::SteadyBar.decode(), 6);
+    /// ```
+    #[must_use]
+    pub fn decode(self) -> u16 {
+        match self {
+            CursorShape::Default => 0,
+            CursorShape::BlinkingBlock => 1,
+            CursorShape::SteadyBlock => 2,
+            CursorShape::BlinkingUnderline => 3,
+            CursorShape::SteadyUnderline => 4,
+            CursorShape::BlinkingBar => 5,
+            CursorShape::SteadyBar => 6,
+        }
+    }
+}
@@ -50,5 +50,11 @@ pub use style::Style;
 mod grapheme;
 pub use grapheme::Grapheme;
 
+mod cursor;
+pub use cursor::CursorShape;
+
+mod snapshot;
+pub use snapshot::{UPDATE_SNAPSHOTS_ENV, assert_snapshot};
+
 
 
@@ -0,0 +1,258 @@
+//! Golden snapshot testing for [`Canvas`](struct.Canvas.html) contents.
+
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use super::{Canvas, Colour, Extended, Style};
+
+/// The environment variable that, if set, makes `assert_snapshot` (re-)write snapshot files
+/// instead of comparing against them
+pub const UPDATE_SNAPSHOTS_ENV: &str = "TALOS_UPDATE_SNAPSHOTS";
+
+/// Symbols used to mark styles in the style grid - `.` is reserved for the default style
+const STYLE_SYMBOLS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+impl Canvas {
+    /// Renders the canvas into a stable, human readable text form
+    ///
+    /// The snapshot consists of the glyph grid, a style grid marking every cell with a symbol,
+    /// and a legend describing the style behind every symbol. Cells in the default style are
+    /// marked with `.`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use talos::render::{CCell, Canvas, Grapheme, Style};
+    ///
+    /// let mut canvas = Canvas::new(3, 1);
+    /// let style = Style::builder().set_bold(true).build();
+    /// canvas.set_ccell(1, 0, CCell { char: Grapheme::new("x"), style });
+    ///
+    /// assert_eq!(
+    ///     canvas.to_snapshot(),
+    ///     "size: 3x1\nglyphs:\n| x |\nstyles:\n|.a.|\nlegend:\na: bold\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_snapshot(&self) -> String {
+        let rect = self.size_rect();
+        let mut styles: Vec<Style> = Vec::new();
+        let mut glyphs = String::new();
+        let mut marks = String::new();
+
+        for y in 0..rect.height {
+            glyphs.push('|');
+            marks.push('|');
+            for x in 0..rect.width {
+                let ccell = self.get_ccell(x, y);
+                glyphs.push_str(ccell.char.as_str());
+
+                if ccell.style == Style::default() {
+                    marks.push('.');
+                    continue;
+                }
+                let idx = if let Some(idx) = styles.iter().position(|s| *s == ccell.style) {
+                    idx
+                } else {
+                    styles.push(ccell.style);
+                    styles.len() - 1
+                };
+                marks.push(STYLE_SYMBOLS.get(idx).map_or('?', |b| char::from(*b)));
+            }
+            glyphs.push_str("|\n");
+            marks.push_str("|\n");
+        }
+
+        let mut out = String::new();
+        let _ = writeln!(out, "size: {}x{}", rect.width, rect.height);
+        out.push_str("glyphs:\n");
+        out.push_str(&glyphs);
+        out.push_str("styles:\n");
+        out.push_str(&marks);
+        out.push_str("legend:\n");
+        for (idx, style) in styles.iter().enumerate() {
+            let symbol = STYLE_SYMBOLS.get(idx).map_or('?', |b| char::from(*b));
+            let _ = writeln!(out, "{symbol}: {}", describe_style(*style));
+        }
+        out
+    }
+}
+
+/// Asserts that the canvas matches the snapshot stored at `path`
+///
+/// Relative paths are resolved against the `CARGO_MANIFEST_DIR` of the crate under test.
+///
+/// If the environment variable `TALOS_UPDATE_SNAPSHOTS` is set, the snapshot file is written
+/// (or overwritten) instead, and the assertion always passes. Use this to create new snapshots and
+/// to accept intended changes.
+///
+/// # Panics
+/// Panics with a line by line diff if the canvas does not match the snapshot, or if the snapshot
+/// file cannot be read or written.
+///
+/// # Example
+/// ```rust,no_run
+/// use talos::render::{Canvas, assert_snapshot};
+///
+/// let canvas = Canvas::new(10, 5);
+/// assert_snapshot("tests/snapshots/empty_canvas.snap", &canvas);
+/// ```
+pub fn assert_snapshot<P: AsRef<Path>>(path: P, canvas: &Canvas) {
+    let path = resolve_path(path.as_ref());
+    let actual = canvas.to_snapshot();
+
+    if std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap_or_else(|e| {
+                panic!(
+                    "Failed to create snapshot directory {}: {e}",
+                    parent.display()
+                )
+            });
+        }
+        std::fs::write(&path, &actual)
+            .unwrap_or_else(|e| panic!("Failed to write snapshot {}: {e}", path.display()));
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "Failed to read snapshot {}: {e}\nRun with `{UPDATE_SNAPSHOTS_ENV}=1` to create it.",
+            path.display()
+        )
+    });
+
+    if let Some(diff) = snapshot_diff(&expected, &actual) {
+        panic!(
+            "Snapshot {} does not match\n{diff}\nRun with `{UPDATE_SNAPSHOTS_ENV}=1` to accept the changes.",
+            path.display()
+        );
+    }
+}
+
+fn resolve_path(path: &Path) -> PathBuf {
+    if path.is_relative()
+        && let Some(manifest_dir) = std::env::var_os("CARGO_MANIFEST_DIR")
+    {
+        return Path::new(&manifest_dir).join(path);
+    }
+    path.to_path_buf()
+}
+
+/// Returns a line by line diff of two snapshots, or `None` if they are equal
+fn snapshot_diff(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    let mut out = String::new();
+
+    for i in 0..expected_lines.len().max(actual_lines.len()) {
+        match (expected_lines.get(i), actual_lines.get(i)) {
+            (Some(e), Some(a)) if e == a => {
+                let _ = writeln!(out, "  {e}");
+            }
+            (e, a) => {
+                if let Some(e) = e {
+                    let _ = writeln!(out, "- {e}");
+                }
+                if let Some(a) = a {
+                    let _ = writeln!(out, "+ {a}");
+                }
+            }
+        }
+    }
+    Some(out)
+}
+
+fn describe_style(style: Style) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(fg) = style.get_fg() {
+        parts.push(format!("fg={}", describe_colour(fg)));
+    }
+    if let Some(bg) = style.get_bg() {
+        parts.push(format!("bg={}", describe_colour(bg)));
+    }
+    let attributes = [
+        (style.get_bold(), "bold"),
+        (style.get_dim(), "dim"),
+        (style.get_italic(), "italic"),
+        (style.get_underline(), "underline"),
+        (style.get_blink(), "blink"),
+        (style.get_reverse(), "reverse"),
+        (style.get_hidden(), "hidden"),
+        (style.get_strikethrough(), "strikethrough"),
+    ];
+    for (set, name) in attributes {
+        if set == Some(true) {
+            parts.push(name.to_string());
+        }
+    }
+    parts.join(" ")
+}
+
+fn describe_colour(colour: Colour) -> String {
+    match colour {
+        Colour::Normal(n) => format!("{n:?}").to_lowercase(),
+        Colour::Bright(b) => format!("bright-{b:?}").to_lowercase(),
+        Colour::Extended(Extended::ColourMode(cm)) => format!("idx({})", cm.decode()),
+        Colour::Extended(Extended::TrueColour(tc)) => {
+            let (r, g, b) = tc.decode();
+            format!("#{r:02x}{g:02x}{b:02x}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{Bright, CCell, Grapheme, Normal, TrueColour};
+
+    #[test]
+    fn test_snapshot_format() {
+        let mut canvas = Canvas::new(4, 2);
+        let red = Style::builder()
+            .set_fg(Colour::Normal(Normal::Red))
+            .set_bg(Colour::Bright(Bright::Blue))
+            .set_italic(true)
+            .build();
+        let rgb = Style::builder()
+            .set_fg(Colour::Extended(Extended::TrueColour(TrueColour::RGB(
+                255, 0, 16,
+            ))))
+            .build();
+        let cell = |ch: &str, style: Style| CCell {
+            char: Grapheme::new(ch),
+            style,
+        };
+        canvas.set_ccell(0, 0, cell("a", red));
+        canvas.set_ccell(1, 0, cell("b", red));
+        canvas.set_ccell(3, 1, cell("c", rgb));
+
+        assert_eq!(
+            canvas.to_snapshot(),
+            "size: 4x2\n\
+             glyphs:\n\
+             |ab  |\n\
+             |   c|\n\
+             styles:\n\
+             |aa..|\n\
+             |...b|\n\
+             legend:\n\
+             a: fg=red bg=bright-blue italic\n\
+             b: fg=#ff0010\n"
+        );
+    }
+
+    #[test]
+    fn test_snapshot_diff() {
+        assert_eq!(snapshot_diff("a\nb\n", "a\nb\n"), None);
+        assert_eq!(
+            snapshot_diff("a\nb\nc\n", "a\nx\n"),
+            Some("  a\n- b\n+ x\n- c\n".to_string())
+        );
+    }
+}
@@ -1,13 +1,26 @@
-use crate::utils::constants::ansi::CONTROL_SEQUENCE_INTRO;
+use crate::utils::{constants::ansi::CONTROL_SEQUENCE_INTRO, push_u16_as_ascii};
 
 use super::Colour;
+use crate::backend::ColourDepth;
 
 mod style_builder;
 pub use style_builder::StyleBuilder;
-use utils::{handle_bg, handle_fg};
+use utils::{handle_bg, handle_fg, parse_colour};
 
 mod utils;
 
+/// Maps the bits of `Style::bit_flag` to their SGR codes
+const ATTRIBUTE_CODES: [(u8, u16); 8] = [
+    (0b1000_0000, 1),
+    (0b0100_0000, 2),
+    (0b0010_0000, 3),
+    (0b0001_0000, 4),
+    (0b0000_1000, 5),
+    (0b0000_0100, 7),
+    (0b0000_0010, 9),
+    (0b0000_0001, 6),
+];
+
 /// Represents a style
 ///
 /// The style is generated from a [`StyleBuilder`](struct.StyleBuilder.html)
@@ -198,6 +211,29 @@ impl Style {
         Some(self.bit_flag & 0b0000_0001 != 0)
     }
 
+    /// Returns the style with its colours replaced by the closest ones a terminal with the given
+    /// colour depth can display
+    ///
+    /// See [`Colour::downgrade`](enum.Colour.html#method.downgrade).
+    ///
+    /// # Example
+    /// ```rust
+    /// use talos::{backend::ColourDepth, render::{Colour, Normal, Style}};
+    ///
+    /// let style = Style::builder().set_fg(Colour::Normal(Normal::Red)).set_bold(true).build();
+    /// let plain = style.downgrade(ColourDepth::Monochrome);
+    /// assert_eq!(plain.get_fg(), None);
+    /// assert_eq!(plain.get_bold(), Some(true));
+    /// ```
+    #[must_use]
+    pub fn downgrade(self, depth: ColourDepth) -> Style {
+        Style {
+            fg: self.fg.and_then(|fg| fg.downgrade(depth)),
+            bg: self.bg.and_then(|bg| bg.downgrade(depth)),
+            ..self
+        }
+    }
+
     /// Generates an ANSI control sequence that transforms the terminal style from `from` to `self`
     pub(crate) fn generate_diff(self, from: Style, output_buffer: &mut Vec<u8>) {
         if self == from {
@@ -238,22 +274,12 @@ impl Style {
 
         let new_bits = self.bit_flag & !from.bit_flag;
         if new_bits != 0 {
-            let bits = [
-                (0b1000_0000, "1"),
-                (0b0100_0000, "2"),
-                (0b0010_0000, "3"),
-                (0b0001_0000, "4"),
-                (0b0000_1000, "5"),
-                (0b0000_0100, "7"),
-                (0b0000_0010, "9"),
-                (0b0000_0001, "6"),
-            ];
-            for (mask, code) in bits {
+            for (mask, code) in ATTRIBUTE_CODES {
                 if new_bits & mask != 0 {
                     if !first {
                         output_buffer.push(b';');
                     }
-                    output_buffer.extend_from_slice(code.as_bytes());
+                    push_u16_as_ascii(output_buffer, code);
                     first = false;
                 }
             }
@@ -316,4 +342,44 @@ impl Style {
         }
         output_buffer.extend_from_slice(b"m");
     }
+
+    /// Applies the parameters of a SGR control sequence (`ESC [ ... m`) to the style
+    ///
+    /// This is the inverse of `generate` and `generate_diff`, and is used to interpret rendered
+    /// output back into cells.
+    pub(crate) fn apply_sgr(&mut self, params: &[u16]) {
+        if params.is_empty() {
+            *self = Style::default();
+            return;
+        }
+
+        let mut i = 0;
+        while i < params.len() {
+            match params[i] {
+                0 => *self = Style::default(),
+                39 => self.fg = None,
+                49 => self.bg = None,
+                code @ (30..=38 | 90..=97) => {
+                    let (colour, consumed) = parse_colour(code, &params[i + 1..]);
+                    if let Some(colour) = colour {
+                        self.fg = Some(colour);
+                    }
+                    i += consumed;
+                }
+                code @ (40..=48 | 100..=107) => {
+                    let (colour, consumed) = parse_colour(code, &params[i + 1..]);
+                    if let Some(colour) = colour {
+                        self.bg = Some(colour);
+                    }
+                    i += consumed;
+                }
+                code => {
+                    if let Some((mask, _)) = ATTRIBUTE_CODES.iter().find(|(_, c)| *c == code) {
+                        self.bit_flag |= mask;
+                    }
+                }
+            }
+            i += 1;
+        }
+    }
 }
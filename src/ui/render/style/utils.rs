@@ -1,6 +1,6 @@
 use crate::{
     render::{
-        Colour,
+        Bright, Colour, ColourMode, Normal, TrueColour,
         colour::{
             BG_PRE_DIGIT_BRIGHT, BG_PRE_DIGIT_NORMAL, COLOURMODE_SIGNAL_BIT, EXTENDED_BG_BIT,
             EXTENDED_FG_BIT, Extended, FG_PRE_DIGIT_BRIGHT, FG_PRE_DIGIT_NORMAL,
@@ -69,3 +69,39 @@ fn handle_colour(colour: Colour, fg: bool, output_buffer: &mut Vec<u8>) {
         },
     }
 }
+
+/// Parses a SGR colour code back into a `Colour`
+///
+/// `rest` are the parameters following `code`, needed for extended colours.
+///
+/// Returns the colour and the number of parameters of `rest` that were consumed.
+pub fn parse_colour(code: u16, rest: &[u16]) -> (Option<Colour>, usize) {
+    let index = u8::try_from(code % 10).unwrap_or_default();
+    match code {
+        30..=37 | 40..=47 => (Normal::from_index(index).map(Colour::Normal), 0),
+        90..=97 | 100..=107 => (Bright::from_index(index).map(Colour::Bright), 0),
+        38 | 48 => match rest {
+            [5, n, ..] => (
+                u8::try_from(*n)
+                    .ok()
+                    .map(|n| Colour::Extended(Extended::ColourMode(ColourMode::RgbBit(n)))),
+                2,
+            ),
+            [2, r, g, b, ..] => {
+                let rgb = (u8::try_from(*r), u8::try_from(*g), u8::try_from(*b));
+                if let (Ok(r), Ok(g), Ok(b)) = rgb {
+                    (
+                        Some(Colour::Extended(Extended::TrueColour(TrueColour::RGB(
+                            r, g, b,
+                        )))),
+                        4,
+                    )
+                } else {
+                    (None, 4)
+                }
+            }
+            _ => (None, rest.len()),
+        },
+        _ => (None, 0),
+    }
+}
@@ -6,6 +6,7 @@
 pub const CONTROL_SEQUENCE_INTRO: &str = "\x1B[";
 
 pub const CLEAR_ALL: &str = "\x1b[2J";
+pub const CLEAR_BELOW: &str = "\x1b[J";
 pub const TO_TOP_LEFT: &str = "\x1b[H";
 
 pub const HIDE_CURSOR: &str = "\x1b[?25l";
@@ -17,6 +18,38 @@ pub const MOUSE_REPORTING_CODE: &str = "\x1b[?1000h";
 pub const MOUSE_FORMATTING_CODE: &str = "\x1b[?1006h";
 pub const DISABLE_MOUSE_REPORTING_CODE: &str = "\x1b[?1000l";
 pub const DISABLE_MOUSE_FORMATTING_CODE: &str = "\x1b[?1006l";
+// The urxvt encoding is a fallback for terminals without the SGR encoding - enable it first
+pub const MOUSE_URXVT_FORMATTING_CODE: &str = "\x1b[?1015h";
+pub const DISABLE_MOUSE_URXVT_FORMATTING_CODE: &str = "\x1b[?1015l";
+pub const MOUSE_DRAG_REPORTING_CODE: &str = "\x1b[?1002h";
+pub const DISABLE_MOUSE_DRAG_REPORTING_CODE: &str = "\x1b[?1002l";
+pub const MOUSE_MOTION_REPORTING_CODE: &str = "\x1b[?1003h";
+pub const DISABLE_MOUSE_MOTION_REPORTING_CODE: &str = "\x1b[?1003l";
+
+pub const ENABLE_BRACKETED_PASTE: &str = "\x1b[?2004h";
+pub const DISABLE_BRACKETED_PASTE: &str = "\x1b[?2004l";
+pub const ENABLE_FOCUS_EVENTS: &str = "\x1b[?1004h";
+pub const DISABLE_FOCUS_EVENTS: &str = "\x1b[?1004l";
+// Pushing the kitty keyboard flags takes them as argument: `ESC [ > flags u`
+pub const POP_KEYBOARD_FLAGS: &str = "\x1b[<u";
 
 pub const BEGIN_SYNC_UPDATE: &str = "\x1b[?2026h";
 pub const END_SYNC_UPDATE: &str = "\x1b[?2026l";
+
+// Capability queries - the primary device attributes query has to come last, as every terminal
+// answers it
+pub const QUERY_SYNC_UPDATE_MODE: &str = "\x1b[?2026$p";
+pub const QUERY_SGR_MOUSE_MODE: &str = "\x1b[?1006$p";
+pub const QUERY_BRACKETED_PASTE_MODE: &str = "\x1b[?2004$p";
+pub const QUERY_FOCUS_EVENTS_MODE: &str = "\x1b[?1004$p";
+pub const QUERY_KEYBOARD_FLAGS: &str = "\x1b[?u";
+pub const QUERY_TERMINAL_VERSION: &str = "\x1b[>0q";
+pub const QUERY_SECONDARY_ATTRIBUTES: &str = "\x1b[>c";
+pub const QUERY_PRIMARY_ATTRIBUTES: &str = "\x1b[c";
+
+// Queries answered with events, see `input::Query`
+pub const QUERY_CURSOR_POSITION: &str = "\x1b[6n";
+pub const QUERY_FOREGROUND_COLOUR: &str = "\x1b]10;?\x1b\\";
+pub const QUERY_BACKGROUND_COLOUR: &str = "\x1b]11;?\x1b\\";
+pub const QUERY_WINDOW_PIXEL_SIZE: &str = "\x1b[14t";
+pub const QUERY_CELL_PIXEL_SIZE: &str = "\x1b[16t";
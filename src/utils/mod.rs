@@ -35,3 +35,30 @@ pub fn move_render_cursor(output: &mut Vec<u8>, x: u16, y: u16) -> TalosResult<(
     write_all_bytes(output, b"H")?;
     Ok(())
 }
+
+/// Moves the cursor relative to the row it is on
+///
+/// Inline viewports do not know their position on the screen, so they cannot use absolute
+/// positioning.
+pub fn move_inline_cursor(output: &mut Vec<u8>, from_row: u16, x: u16, y: u16) -> TalosResult<()> {
+    if y != from_row {
+        write_all_bytes(output, &[0x1b, b'['])?;
+        push_u16_as_ascii(output, y.abs_diff(from_row));
+        write_all_bytes(output, if y < from_row { b"A" } else { b"B" })?;
+    }
+    write_all_bytes(output, b"\r")?;
+    if x > 0 {
+        write_all_bytes(output, &[0x1b, b'['])?;
+        push_u16_as_ascii(output, x);
+        write_all_bytes(output, b"C")?;
+    }
+    Ok(())
+}
+
+/// Sets the shape of the cursor (`DECSCUSR`)
+pub fn set_cursor_shape(output: &mut Vec<u8>, shape: u16) -> TalosResult<()> {
+    write_all_bytes(output, &[0x1b, b'['])?;
+    push_u16_as_ascii(output, shape);
+    write_all_bytes(output, b" q")?;
+    Ok(())
+}
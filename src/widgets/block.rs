@@ -725,4 +725,25 @@ mod tests {
         let trait_regions = widget_ref.inner(area);
         assert_eq!(trait_regions, vec![Rect::new(1, 1, 8, 8)]);
     }
+
+    #[test]
+    fn test_block_snapshot() {
+        use crate::widgets::traits::Widget;
+        let mut canvas = Canvas::new(16, 5);
+        let thoth = thoth::Thoth::new().unwrap();
+        let style = Style::builder()
+            .set_fg(crate::render::Colour::Normal(crate::render::Normal::Yellow))
+            .set_bg(crate::render::Colour::Normal(crate::render::Normal::Blue))
+            .build();
+
+        let mut block = Block::new()
+            .title("Title", &thoth, false)
+            .with_fat_border()
+            .with_beautify_border_breaks()
+            .with_bg_fill();
+        block.style(style);
+        block.render(&mut canvas, Rect::new(1, 0, 14, 5), &thoth);
+
+        crate::render::assert_snapshot("tests/snapshots/block_fat_title.snap", &canvas);
+    }
 }
@@ -0,0 +1,460 @@
+use crate::input::Keymap;
+use crate::layout::Rect;
+use crate::render::{CCell, Canvas, Grapheme, Style};
+use crate::widgets::traits::Widget;
+
+/// A key binding to hint at, e.g. `F1` - `Help`
+///
+/// # Example
+/// ```rust
+/// use talos::widgets::KeyHint;
+///
+/// let hint = KeyHint::new("F1", "Help").with_category("General").with_priority(10);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct KeyHint {
+    /// The keys to press, e.g. `Ctrl+x Ctrl+s`
+    pub keys: String,
+    /// What the keys do
+    pub description: String,
+    /// The category the hint is grouped under in the help overlay
+    pub category: Option<String>,
+    /// Hints with a lower priority are left out first when the hint bar is too narrow
+    pub priority: u8,
+}
+
+impl KeyHint {
+    /// Creates a new key hint, without a category and with priority 0
+    pub fn new(keys: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            keys: keys.into(),
+            description: description.into(),
+            category: None,
+            priority: 0,
+        }
+    }
+
+    /// Sets the category the hint is grouped under in the help overlay
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    /// Sets the priority of the hint - the higher, the longer it stays in a narrow hint bar
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Creates hints for the bindings currently active in `keymap`
+    ///
+    /// `describe` is called with the keys and the action of every active binding. Bindings it
+    /// returns `None` for are left out.
+    ///
+    /// # Example
+    /// ```rust
+    /// use talos::{input::Keymap, widgets::KeyHint};
+    ///
+    /// let mut keymap = Keymap::new();
+    /// keymap.bind(Keymap::<&str>::GLOBAL, "f1", "Help").unwrap();
+    ///
+    /// let hints = KeyHint::from_keymap(&keymap, |keys, action| Some(KeyHint::new(keys, *action)));
+    /// assert_eq!(hints, vec![KeyHint::new("F1", "Help")]);
+    /// ```
+    pub fn from_keymap<A, F>(keymap: &Keymap<A>, mut describe: F) -> Vec<KeyHint>
+    where
+        F: FnMut(String, &A) -> Option<KeyHint>,
+    {
+        keymap
+            .active_bindings()
+            .into_iter()
+            .filter_map(|binding| {
+                let keys = binding
+                    .keys
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                describe(keys, &binding.action)
+            })
+            .collect()
+    }
+}
+
+/// A key hints widget
+///
+/// Renders a single line of key hints like `F1 Help | Esc Exit` into the top row of its area.
+/// If the area is too narrow, the hints with the lowest priority are left out.
+///
+/// With the overlay shown, all hints are listed over the whole area instead, grouped by category -
+/// e.g. as a help screen.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use talos::{
+///     Talos,
+///     input::Keymap,
+///     layout::Rect,
+///     widgets::{KeyHint, KeyHints, traits::Widget},
+/// };
+///
+/// fn main() -> Result<(), talos::TalosError> {
+///     let mut talos = Talos::builder().build()?;
+///     let mut keymap = Keymap::new();
+///     keymap.bind(Keymap::<&str>::GLOBAL, "f1", "Help")?;
+///     keymap.bind(Keymap::<&str>::GLOBAL, "esc", "Exit")?;
+///
+///     talos.begin_frame();
+///     let (canvas, thoth) = talos.render_ctx();
+///
+///     let hints = KeyHint::from_keymap(&keymap, |keys, action| Some(KeyHint::new(keys, *action)));
+///     let mut key_hints = KeyHints::new(hints);
+///     key_hints.render(canvas, Rect::new(0, 0, 40, 1), thoth);
+///
+///     talos.present()?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct KeyHints {
+    hints: Vec<KeyHint>,
+    style: Style,
+    key_style: Style,
+    separator: String,
+    overlay: bool,
+}
+
+impl Default for KeyHints {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl KeyHints {
+    /// Creates a new key hints widget
+    pub fn new(hints: Vec<KeyHint>) -> Self {
+        Self {
+            hints,
+            style: Style::default(),
+            key_style: Style::builder().set_bold(true).build(),
+            separator: " | ".to_string(),
+            overlay: false,
+        }
+    }
+
+    /// Replaces the hints, e.g. after the mode of the keymap changed
+    pub fn set_hints(&mut self, hints: Vec<KeyHint>) {
+        self.hints = hints;
+    }
+
+    /// Returns the hints
+    pub fn hints(&self) -> &[KeyHint] {
+        &self.hints
+    }
+
+    /// Sets the style of the keys - the default is bold
+    pub fn with_key_style(mut self, style: Style) -> Self {
+        self.key_style = style;
+        self
+    }
+
+    /// Sets the separator between hints in the hint bar - the default is `" | "`
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Shows or hides the help overlay
+    pub fn set_overlay(&mut self, overlay: bool) {
+        self.overlay = overlay;
+    }
+
+    /// Toggles the help overlay
+    pub fn toggle_overlay(&mut self) {
+        self.overlay = !self.overlay;
+    }
+
+    /// Returns whether the help overlay is shown
+    #[must_use]
+    pub fn is_overlay(&self) -> bool {
+        self.overlay
+    }
+
+    /// Returns the indices of the hints fitting into `width`, leaving out the lowest priorities
+    fn visible_hints(&self, width: u16, thoth: &thoth::Thoth) -> Vec<usize> {
+        let separator_width = glyphs(&self.separator, thoth).len();
+        let hint_width = |hint: &KeyHint| {
+            glyphs(&hint.keys, thoth).len() + 1 + glyphs(&hint.description, thoth).len()
+        };
+
+        let mut visible: Vec<usize> = (0..self.hints.len()).collect();
+        let mut total = visible
+            .iter()
+            .map(|&i| hint_width(&self.hints[i]))
+            .sum::<usize>()
+            + separator_width * visible.len().saturating_sub(1);
+        while total > usize::from(width) && visible.len() > 1 {
+            // The last hint with the lowest priority goes first
+            let Some(position) = visible
+                .iter()
+                .enumerate()
+                .rev()
+                .min_by_key(|(_, i)| self.hints[**i].priority)
+                .map(|(position, _)| position)
+            else {
+                break;
+            };
+            total -= hint_width(&self.hints[visible.remove(position)]) + separator_width;
+        }
+        visible
+    }
+
+    fn render_bar(&self, canvas: &mut Canvas, area: Rect, thoth: &thoth::Thoth) {
+        let mut line = Line::new(canvas, area, area.top());
+        line.fill(self.style);
+        for (n, index) in self
+            .visible_hints(area.width, thoth)
+            .into_iter()
+            .enumerate()
+        {
+            let hint = &self.hints[index];
+            if n > 0 {
+                line.write(&self.separator, self.style, thoth);
+            }
+            line.write(&hint.keys, self.key_style, thoth);
+            line.write(" ", self.style, thoth);
+            line.write(&hint.description, self.style, thoth);
+        }
+    }
+
+    fn render_overlay(&self, canvas: &mut Canvas, area: Rect, thoth: &thoth::Thoth) {
+        // Categories in the order they first appear, uncategorised hints first
+        let mut categories: Vec<Option<&str>> = vec![None];
+        for hint in &self.hints {
+            let category = hint.category.as_deref();
+            if !categories.contains(&category) {
+                categories.push(category);
+            }
+        }
+        let keys_width = self
+            .hints
+            .iter()
+            .map(|hint| glyphs(&hint.keys, thoth).len())
+            .max()
+            .unwrap_or(0);
+        let heading_style = self.key_style.new_from_self().set_underline(true).build();
+
+        for y in area.top()..area.bottom() {
+            Line::new(canvas, area, y).fill(self.style);
+        }
+        let mut y = area.top();
+        for category in categories {
+            let hints: Vec<&KeyHint> = self
+                .hints
+                .iter()
+                .filter(|hint| hint.category.as_deref() == category)
+                .collect();
+            if hints.is_empty() {
+                continue;
+            }
+            if y > area.top() {
+                // A blank line between categories
+                y = y.saturating_add(1);
+            }
+            if let Some(category) = category {
+                Line::new(canvas, area, y).write(category, heading_style, thoth);
+                y = y.saturating_add(1);
+            }
+            for hint in hints {
+                let mut line = Line::new(canvas, area, y);
+                line.write("  ", self.style, thoth);
+                line.write(&hint.keys, self.key_style, thoth);
+                let padding = keys_width - glyphs(&hint.keys, thoth).len() + 2;
+                line.write(&" ".repeat(padding), self.style, thoth);
+                line.write(&hint.description, self.style, thoth);
+                y = y.saturating_add(1);
+            }
+        }
+    }
+}
+
+impl Widget for KeyHints {
+    fn style(&mut self, style: Style) {
+        self.style = style;
+    }
+    fn render(&mut self, canvas: &mut Canvas, area: Rect, thoth: &thoth::Thoth) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        if self.overlay {
+            self.render_overlay(canvas, area, thoth);
+        } else {
+            self.render_bar(canvas, area, thoth);
+        }
+    }
+}
+
+fn glyphs(text: &str, thoth: &thoth::Thoth) -> Vec<Grapheme> {
+    thoth
+        .segment(text)
+        .unwrap_or_else(|_| text.chars().map(|ch| ch.to_string()).collect())
+        .iter()
+        .map(|g| Grapheme::new(g))
+        .collect()
+}
+
+/// Writes text into a single row of an area, clipping it at the right edge
+struct Line<'a> {
+    canvas: &'a mut Canvas,
+    area: Rect,
+    x: u16,
+    y: u16,
+}
+
+impl<'a> Line<'a> {
+    fn new(canvas: &'a mut Canvas, area: Rect, y: u16) -> Self {
+        Self {
+            canvas,
+            area,
+            x: area.left(),
+            y,
+        }
+    }
+
+    fn fill(&mut self, style: Style) {
+        if self.y >= self.area.bottom() {
+            return;
+        }
+        for x in self.area.left()..self.area.right() {
+            self.canvas.set_ccell(
+                x,
+                self.y,
+                CCell {
+                    char: Grapheme::default(),
+                    style,
+                },
+            );
+        }
+    }
+
+    fn write(&mut self, text: &str, style: Style, thoth: &thoth::Thoth) {
+        for glyph in glyphs(text, thoth) {
+            if self.x < self.area.right() && self.y < self.area.bottom() {
+                self.canvas
+                    .set_ccell(self.x, self.y, CCell { char: glyph, style });
+            }
+            self.x = self.x.saturating_add(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(canvas: &Canvas) -> Vec<String> {
+        let rect = canvas.size_rect();
+        (0..rect.height)
+            .map(|y| {
+                (0..rect.width)
+                    .map(|x| canvas.get_ccell(x, y).char.as_str().to_string())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn hints() -> Vec<KeyHint> {
+        vec![
+            KeyHint::new("F1", "Help").with_priority(2),
+            KeyHint::new("g g", "Top").with_category("Move"),
+            KeyHint::new("Ctrl+s", "Save")
+                .with_category("File")
+                .with_priority(1),
+            KeyHint::new("G", "Bottom").with_category("Move"),
+        ]
+    }
+
+    #[test]
+    fn test_hint_bar_truncates_by_priority() {
+        let thoth = thoth::Thoth::new().unwrap();
+        let mut key_hints = KeyHints::new(hints());
+
+        let mut canvas = Canvas::new(50, 1);
+        let area = canvas.size_rect();
+        key_hints.render(&mut canvas, area, &thoth);
+        assert_eq!(
+            rows(&canvas),
+            vec!["F1 Help | g g Top | Ctrl+s Save | G Bottom"]
+        );
+        assert_eq!(canvas.get_ccell(0, 0).style, key_hints.key_style);
+        assert_eq!(canvas.get_ccell(3, 0).style, Style::default());
+
+        let mut canvas = Canvas::new(30, 1);
+        let area = canvas.size_rect();
+        key_hints.render(&mut canvas, area, &thoth);
+        assert_eq!(rows(&canvas), vec!["F1 Help | Ctrl+s Save"]);
+
+        let mut canvas = Canvas::new(5, 1);
+        let area = canvas.size_rect();
+        key_hints.render(&mut canvas, area, &thoth);
+        assert_eq!(rows(&canvas), vec!["F1 He"]);
+    }
+
+    #[test]
+    fn test_help_overlay() {
+        let thoth = thoth::Thoth::new().unwrap();
+        let mut key_hints = KeyHints::new(hints());
+        key_hints.toggle_overlay();
+        assert!(key_hints.is_overlay());
+
+        let mut canvas = Canvas::new(20, 10);
+        let area = canvas.size_rect();
+        key_hints.render(&mut canvas, area, &thoth);
+        assert_eq!(
+            rows(&canvas),
+            vec![
+                "  F1      Help",
+                "",
+                "Move",
+                "  g g     Top",
+                "  G       Bottom",
+                "",
+                "File",
+                "  Ctrl+s  Save",
+                "",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_keymap() {
+        let mut keymap = Keymap::new();
+        keymap
+            .bind(Keymap::<&str>::GLOBAL, "ctrl+x ctrl+s", "save")
+            .unwrap();
+        keymap.bind(Keymap::<&str>::GLOBAL, "q", "quit").unwrap();
+        keymap.bind("popup", "q", "close").unwrap();
+        keymap
+            .bind(Keymap::<&str>::GLOBAL, "x", "internal")
+            .unwrap();
+        keymap.push_layer("popup");
+
+        let hints = KeyHint::from_keymap(&keymap, |keys, action| {
+            (*action != "internal").then(|| KeyHint::new(keys, *action))
+        });
+        assert_eq!(
+            hints,
+            vec![
+                KeyHint::new("q", "close"),
+                KeyHint::new("Ctrl+x Ctrl+s", "save"),
+            ]
+        );
+    }
+}
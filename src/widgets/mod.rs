@@ -31,10 +31,12 @@
 mod area;
 mod block;
 mod internal_text;
+mod key_hints;
 mod number;
 mod text;
 pub use area::Area;
 pub use block::Block;
+pub use key_hints::{KeyHint, KeyHints};
 pub use number::Number;
 pub use text::Text;
 
@@ -485,4 +485,27 @@ mod tests {
         assert_eq!(regions[1], Rect::new(0, 0, 10, 2));
         assert_eq!(regions[2], Rect::new(0, 2, 10, 2));
     }
+
+    #[test]
+    fn test_list_snapshot() {
+        let thoth = thoth::Thoth::new().unwrap();
+        let mut canvas = crate::render::Canvas::new(12, 4);
+        let mut state = ListState {
+            selected: Some(1),
+            ..ListState::default()
+        };
+        let items: Vec<Box<dyn Widget>> = vec![
+            Box::new(Text::new("Item 1", &thoth)) as Box<dyn Widget>,
+            Box::new(Text::new("Item 2", &thoth)) as Box<dyn Widget>,
+            Box::new(Text::new("Item 3", &thoth)) as Box<dyn Widget>,
+        ];
+        let selected_style = Style::builder().set_reverse(true).build();
+        let mut list = List::new(&mut state, items)
+            .with_selected_symbol('>', &thoth)
+            .with_selected_style(selected_style);
+
+        list.render(&mut canvas, Rect::new(0, 0, 12, 4), &thoth);
+
+        crate::render::assert_snapshot("tests/snapshots/list_selected.snap", &canvas);
+    }
 }
@@ -1108,4 +1108,31 @@ mod tests {
         assert_eq!(regions[3], Rect::new(1, 7, 9, 3));
         assert_eq!(regions[4], Rect::new(11, 7, 8, 3));
     }
+
+    #[test]
+    fn test_table_snapshot() {
+        let mut table_state = TableState::new();
+        let thoth = thoth::Thoth::new().unwrap();
+        let mut canvas = Canvas::new(21, 7);
+        let r1: Vec<Box<dyn Widget>> = vec![
+            Box::new(Text::new("Name", &thoth)),
+            Box::new(Text::new("Value", &thoth)),
+        ];
+        let r2: Vec<Box<dyn Widget>> = vec![
+            Box::new(Text::new("R2C1", &thoth)),
+            Box::new(Text::new("R2C2", &thoth)),
+        ];
+        let header_style = Style::builder().set_bold(true).build();
+
+        let mut table = Table::new(&mut table_state)
+            .with_rows(vec![r1, r2])
+            .with_header_row(0)
+            .with_header_style(header_style)
+            .draw_inner_border(InnerBorder::All)
+            .draw_outer_border();
+
+        table.render(&mut canvas, Rect::new(0, 0, 21, 7), &thoth);
+
+        crate::render::assert_snapshot("tests/snapshots/table_all_borders.snap", &canvas);
+    }
 }
@@ -0,0 +1,46 @@
+use talos::{
+    Present, Talos, TalosError,
+    backend::TestBackend,
+    layout::Rect,
+    widgets::{Text, traits::Widget},
+};
+
+// Signals are process wide - this binary holds a single test, so no other `Talos` consumes them
+
+fn draw(talos: &mut Talos, text: &str) -> Result<Present, TalosError> {
+    talos.begin_frame();
+    let (canvas, thoth) = talos.render_ctx();
+    Text::new(text, thoth).render(canvas, Rect::new(0, 0, 10, 1), thoth);
+    talos.present()
+}
+
+#[test]
+fn test_continue_without_suspend() -> Result<(), TalosError> {
+    let backend = TestBackend::new(10, 5);
+    // Keeping the panic handler also registers the signal handlers
+    let mut talos = Talos::builder()
+        .with_backend(Box::new(backend.clone()))
+        .with_inline_viewport(2)
+        .build()?;
+    draw(&mut talos, "Hello")?;
+    backend.clear_written();
+
+    // Stopped and continued by someone else - the viewport is redrawn, not reserved again
+    unsafe { libc::raise(libc::SIGCONT) };
+    assert!(matches!(draw(&mut talos, "Hello")?, Present::Presented));
+    assert!(!backend.written().contains(&b'\n'));
+    assert_eq!(backend.line(0).trim_end(), "Hello");
+    assert!(backend.is_entered());
+
+    // Suspended by the application - a stray `SIGCONT` must not take the terminal over
+    talos.suspend()?;
+    backend.clear_written();
+    unsafe { libc::raise(libc::SIGCONT) };
+    talos.poll_input()?;
+    assert!(backend.written().is_empty());
+    assert!(!backend.is_entered());
+
+    talos.resume()?;
+    assert!(backend.is_entered());
+    Ok(())
+}
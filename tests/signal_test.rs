@@ -0,0 +1,32 @@
+use talos::{Talos, TalosError, backend::TestBackend, input::Event};
+
+// Signals are process wide - this binary holds a single test, so no other `Talos` consumes them
+
+fn terminations(talos: &mut Talos) -> Result<usize, TalosError> {
+    let events = talos.wait_input(None)?.unwrap_or_default();
+    Ok(events.iter().filter(|e| **e == Event::Terminate).count())
+}
+
+#[test]
+fn test_terminate_event_per_signal() -> Result<(), TalosError> {
+    let backend = TestBackend::new(10, 3);
+    // Keeping the panic handler also registers the signal handlers
+    let mut talos = Talos::builder()
+        .with_backend(Box::new(backend.clone()))
+        .with_terminate_event()
+        .build()?;
+
+    unsafe { libc::raise(libc::SIGINT) };
+    assert_eq!(terminations(&mut talos)?, 1);
+    let waits = backend.waits();
+
+    // Handled - the next call waits for input again, without reporting anything
+    assert_eq!(terminations(&mut talos)?, 0);
+    assert_eq!(backend.waits(), waits + 1);
+
+    // A second signal is reported as well
+    unsafe { libc::raise(libc::SIGTERM) };
+    assert_eq!(terminations(&mut talos)?, 1);
+    assert_eq!(terminations(&mut talos)?, 0);
+    Ok(())
+}